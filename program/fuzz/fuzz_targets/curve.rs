@@ -0,0 +1,28 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use main::schema::{pool::Pool, pool_trait::Exchange};
+use solana_program::pubkey::Pubkey;
+
+// The product of the reserves falls short by less than one unit across a swap plus its fee
+fuzz_target!(|input: (u64, u64, u64, u64, u64)| {
+  let (reserve_a, reserve_b, bid_amount, fee_ratio, tax_ratio) = input;
+  let pool = Pool {
+    mint_a: Pubkey::new_from_array([1; 32]),
+    reserve_a,
+    mint_b: Pubkey::new_from_array([2; 32]),
+    reserve_b,
+    fee_ratio,
+    tax_ratio,
+    ..Pool::default()
+  };
+  let k = reserve_a as u128 * reserve_b as u128;
+  if let Some((ask_amount, new_reserve_a, new_reserve_b)) =
+    pool.curve(bid_amount, &pool.mint_a, &pool.mint_b)
+  {
+    assert_eq!(ask_amount + new_reserve_b, reserve_b);
+    assert!(new_reserve_a as u128 * new_reserve_b as u128 + new_reserve_a as u128 > k);
+    if let Some((_, fee, _)) = pool.fee(ask_amount) {
+      assert!(new_reserve_a as u128 * (new_reserve_b + fee) as u128 + new_reserve_a as u128 > k);
+    }
+  }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use main::schema::{pool::Pool, pool_trait::Exchange};
+
+// Depositing then withdrawing the minted LP tokens returns at most one unit more than was put in
+fuzz_target!(|input: (u64, u64, u64, u64, u64)| {
+  let (reserve_a, reserve_b, liquidity, delta_a, delta_b) = input;
+  let pool = Pool {
+    reserve_a,
+    reserve_b,
+    ..Pool::default()
+  };
+  if let Some((a, b, lpt, new_reserve_a, new_reserve_b, new_liquidity)) =
+    pool.deposit(delta_a, delta_b, liquidity)
+  {
+    assert!(a <= delta_a && b <= delta_b);
+    let pool = Pool {
+      reserve_a: new_reserve_a,
+      reserve_b: new_reserve_b,
+      ..Pool::default()
+    };
+    if let Some((withdrawn_a, withdrawn_b, _, _, _, _)) = pool.withdraw(lpt, new_liquidity) {
+      assert!(withdrawn_a <= a.saturating_add(1) && withdrawn_b <= b.saturating_add(1));
+    }
+  }
+});
@@ -0,0 +1,13 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use main::schema::pool::extract;
+
+// The extracted amounts fit the inputs and the ratio of the reserves
+fuzz_target!(|input: (u64, u64, u64, u64)| {
+  let (a, b, reserve_a, reserve_b) = input;
+  if let Some((a_star, b_star)) = extract(a, b, reserve_a, reserve_b) {
+    assert!(a_star <= a && b_star <= b);
+    assert!(a_star as u128 * reserve_b as u128 <= b as u128 * reserve_a as u128);
+    assert!(b_star as u128 * reserve_a as u128 <= a as u128 * reserve_b as u128);
+  }
+});
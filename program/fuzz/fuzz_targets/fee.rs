@@ -0,0 +1,26 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use main::schema::{pool::Pool, pool_trait::Exchange};
+
+// The fee and tax are carved out of the ask amount, never more
+fuzz_target!(|input: (u64, u64, u64, u64)| {
+  let (ask_amount, fee_ratio, tax_ratio, discount) = input;
+  let pool = Pool {
+    fee_ratio,
+    tax_ratio,
+    ..Pool::default()
+  };
+  if let Some((amount, fee, tax)) = pool.fee(ask_amount) {
+    assert_eq!(
+      amount as u128 + fee as u128 + tax as u128,
+      ask_amount as u128
+    );
+  }
+  if let Some((amount, fee, tax)) = pool.discounted_fee(ask_amount, discount, false) {
+    assert_eq!(
+      amount as u128 + fee as u128 + tax as u128,
+      ask_amount as u128
+    );
+  }
+  pool.flash_fee(ask_amount);
+});
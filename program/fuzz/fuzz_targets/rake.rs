@@ -0,0 +1,45 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use main::{
+  processor::add_sided_liquidity::rake,
+  schema::{pool::Pool, pool_state::PoolState},
+};
+use solana_program::{account_info::AccountInfo, program_pack::Pack, pubkey::Pubkey};
+
+// The amount to swap of a sided deposit never exceeds the deposit
+fuzz_target!(|input: (u64, u64, u64, u64, u64)| {
+  let (amount, reserve_a, reserve_b, fee_ratio, tax_ratio) = input;
+  let pool = Pool {
+    state: PoolState::Initialized,
+    mint_a: Pubkey::new_from_array([1; 32]),
+    reserve_a,
+    mint_b: Pubkey::new_from_array([2; 32]),
+    reserve_b,
+    fee_ratio,
+    tax_ratio,
+    ..Pool::default()
+  };
+  let mut data = vec![0; Pool::LEN];
+  Pool::pack(pool, &mut data).unwrap();
+  let (key, owner) = (
+    Pubkey::new_from_array([3; 32]),
+    Pubkey::new_from_array([4; 32]),
+  );
+  let mut lamports = 0;
+  let pool_acc = AccountInfo::new(
+    &key,
+    false,
+    true,
+    &mut lamports,
+    &mut data,
+    &owner,
+    false,
+    0,
+  );
+  if let Some(bid_amount) = rake(amount, &pool.mint_a, &pool.mint_b, &pool_acc) {
+    assert!(bid_amount <= amount);
+  }
+  if let Some(bid_amount) = rake(amount, &pool.mint_b, &pool.mint_a, &pool_acc) {
+    assert!(bid_amount <= amount);
+  }
+});
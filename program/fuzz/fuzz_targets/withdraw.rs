@@ -0,0 +1,23 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use main::schema::{pool::Pool, pool_trait::Exchange};
+
+// A withdrawal is the pro rata share of the reserves, up to one unit of rounding
+fuzz_target!(|input: (u64, u64, u64, u64)| {
+  let (reserve_a, reserve_b, liquidity, lpt) = input;
+  let pool = Pool {
+    reserve_a,
+    reserve_b,
+    ..Pool::default()
+  };
+  if let Some((delta_a, delta_b, _, new_reserve_a, new_reserve_b, new_liquidity)) =
+    pool.withdraw(lpt, liquidity)
+  {
+    assert_eq!(delta_a + new_reserve_a, reserve_a);
+    assert_eq!(delta_b + new_reserve_b, reserve_b);
+    assert_eq!(new_liquidity + lpt, liquidity);
+    let share = |reserve: u64| lpt as u128 * reserve as u128 + liquidity as u128;
+    assert!((delta_a as u128 * liquidity as u128) < share(reserve_a));
+    assert!((delta_b as u128 * liquidity as u128) < share(reserve_b));
+  }
+});
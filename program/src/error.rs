@@ -35,6 +35,30 @@ pub enum AppError {
   ExceedLimit,
   #[error("Frozen pool")]
   FrozenPool,
+  #[error("Fee or tax ratio is out of bounds")]
+  InvalidRatio,
+  #[error("Invalid multisig configuration")]
+  InvalidMultisig,
+  #[error("Not enough multisig signers")]
+  NotEnoughSigners,
+  #[error("Cannot close a pool with outstanding liquidity")]
+  NonEmptyPool,
+  #[error("Invalid route")]
+  InvalidRoute,
+  #[error("The pool is locked")]
+  LockedPool,
+  #[error("The flash loan was not repaid")]
+  UnpaidFlashLoan,
+  #[error("Invalid tax distribution")]
+  InvalidDistribution,
+  #[error("Invalid fee tiers")]
+  InvalidFeeTiers,
+  #[error("Invalid allowlist")]
+  InvalidAllowlist,
+  #[error("Invalid referrers")]
+  InvalidReferrers,
+  #[error("Invalid stake")]
+  InvalidStake,
 }
 
 impl From<AppError> for ProgramError {
@@ -66,6 +90,18 @@ impl PrintProgramError for AppError {
       AppError::SameMint => msg!("Error: Cannot operate a pool with two same mints"),
       AppError::ExceedLimit => msg!("Error: Exceed limit"),
       AppError::FrozenPool => msg!("Error: Frozen pool"),
+      AppError::InvalidRatio => msg!("Error: Fee or tax ratio is out of bounds"),
+      AppError::InvalidMultisig => msg!("Error: Invalid multisig configuration"),
+      AppError::NotEnoughSigners => msg!("Error: Not enough multisig signers"),
+      AppError::NonEmptyPool => msg!("Error: Cannot close a pool with outstanding liquidity"),
+      AppError::InvalidRoute => msg!("Error: Invalid route"),
+      AppError::LockedPool => msg!("Error: The pool is locked"),
+      AppError::UnpaidFlashLoan => msg!("Error: The flash loan was not repaid"),
+      AppError::InvalidDistribution => msg!("Error: Invalid tax distribution"),
+      AppError::InvalidFeeTiers => msg!("Error: Invalid fee tiers"),
+      AppError::InvalidAllowlist => msg!("Error: Invalid allowlist"),
+      AppError::InvalidReferrers => msg!("Error: Invalid referrers"),
+      AppError::InvalidStake => msg!("Error: Invalid stake"),
     }
   }
 }
@@ -1,10 +1,14 @@
 use crate::error::AppError;
 use crate::interfaces::{xsplata::XSPLATA, xsplt::XSPLT, xsystem::XSystem};
+use crate::schema::multisig::Multisig;
 use solana_program::{
   account_info::AccountInfo,
   entrypoint::ProgramResult,
+  instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT},
+  program_error::ProgramError,
   program_pack::{IsInitialized, Pack},
   pubkey::{Pubkey, PubkeyError},
+  sysvar::instructions::{self, load_current_index_checked, load_instruction_at_checked},
 };
 use spl_token::state::Account;
 
@@ -94,6 +98,22 @@ pub fn is_signer(accounts: &[&AccountInfo]) -> ProgramResult {
   Ok(())
 }
 
+///
+/// Verify an authority that can be either a plain signer
+/// or a multisig account owned by this program with enough signers
+///
+pub fn is_authority(
+  program_id: &Pubkey,
+  authority: &AccountInfo,
+  signers: &[AccountInfo],
+) -> ProgramResult {
+  if authority.owner == program_id && authority.data_len() == Multisig::LEN {
+    let multisig_data = Multisig::unpack(&authority.data.borrow())?;
+    return multisig_data.is_approved(signers);
+  }
+  is_signer(&[authority])
+}
+
 pub fn safe_seed(
   seed_acc: &AccountInfo,
   expected_acc: &AccountInfo,
@@ -106,3 +126,19 @@ pub fn safe_seed(
   }
   Ok(seed)
 }
+
+///
+/// The program that invoked this one, when invoked by a CPI straight from a transaction
+/// instruction (None otherwise). That program is the one of the running top-level instruction.
+///
+pub fn calling_program(instructions_acc: &AccountInfo) -> Result<Option<Pubkey>, ProgramError> {
+  if !instructions::check_id(instructions_acc.key) {
+    return Err(AppError::IncorrectProgramId.into());
+  }
+  if get_stack_height() != TRANSACTION_LEVEL_STACK_HEIGHT + 1 {
+    return Ok(None);
+  }
+  let index = load_current_index_checked(instructions_acc)?;
+  let instruction = load_instruction_at_checked(index as usize, instructions_acc)?;
+  Ok(Some(instruction.program_id))
+}
@@ -1,7 +1,73 @@
 use crate::error::AppError;
+use crate::schema::pool_state::PAUSE_ALL;
 use solana_program::program_error::ProgramError;
 use std::convert::TryInto;
 
+///
+/// Pause mask (all operations if omitted)
+///
+fn unpack_mask(rest: &[u8]) -> Result<u8, ProgramError> {
+  let mask = match rest.get(..1) {
+    Some(slice) => slice[0],
+    None => PAUSE_ALL,
+  };
+  if mask == 0 || mask & !PAUSE_ALL != 0 {
+    return Err(AppError::InvalidInstruction.into());
+  }
+  Ok(mask)
+}
+
+///
+/// A trailing list of u64
+///
+fn unpack_u64s(rest: &[u8]) -> Result<Vec<u64>, ProgramError> {
+  if rest.len() % 8 != 0 {
+    return Err(AppError::InvalidInstruction.into());
+  }
+  rest
+    .chunks(8)
+    .map(|slice| {
+      slice
+        .try_into()
+        .ok()
+        .map(u64::from_le_bytes)
+        .ok_or(AppError::InvalidInstruction)
+    })
+    .collect::<Result<_, AppError>>()
+    .map_err(ProgramError::from)
+}
+
+///
+/// A trailing list of u16
+///
+fn unpack_u16s(rest: &[u8]) -> Result<Vec<u16>, ProgramError> {
+  if rest.len() % 2 != 0 {
+    return Err(AppError::InvalidInstruction.into());
+  }
+  rest
+    .chunks(2)
+    .map(|slice| {
+      slice
+        .try_into()
+        .ok()
+        .map(u16::from_le_bytes)
+        .ok_or(AppError::InvalidInstruction)
+    })
+    .collect::<Result<_, AppError>>()
+    .map_err(ProgramError::from)
+}
+
+///
+/// A trailing list of (u64, u64)
+///
+fn unpack_u64_pairs(rest: &[u8]) -> Result<Vec<(u64, u64)>, ProgramError> {
+  let values = unpack_u64s(rest)?;
+  if values.len() % 2 != 0 {
+    return Err(AppError::InvalidInstruction.into());
+  }
+  Ok(values.chunks(2).map(|pair| (pair[0], pair[1])).collect())
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum AppInstruction {
   InitializePool {
@@ -20,23 +86,112 @@ pub enum AppInstruction {
   Swap {
     amount: u64,
     limit: u64,
+    // Share of the fee to the referrer (0 if omitted)
+    referral_ratio: u64,
+  },
+  FreezePool {
+    mask: u8,
+  },
+  ThawPool {
+    mask: u8,
   },
-  FreezePool,
-  ThawPool,
   TransferTaxman,
   TransferOwnership,
   Route {
     amount: u64,
     limit: u64,
+    // Minimum output of each hop (none, or one per hop)
+    hop_limits: Vec<u64>,
   },
   UpdateFee {
     fee_ratio: u64,
     tax_ratio: u64,
+    // Unchanged if omitted
+    max_referral_ratio: Option<u64>,
   },
   AddSidedLiquidity {
     delta_a: u64,
     delta_b: u64,
   },
+  TransferPauser,
+  TransferFeeManager,
+  InitializeMultisig {
+    m: u8,
+  },
+  EmergencyWithdraw {
+    lpt: u64,
+  },
+  Sync,
+  Skim,
+  GetDrift,
+  ClosePool,
+  SplitRoute {
+    limit: u64,
+    // (amount, hops) of each path
+    paths: Vec<(u64, u8)>,
+  },
+  FlashLoan {
+    amount_a: u64,
+    amount_b: u64,
+    // Callback instruction data
+    data: Vec<u8>,
+  },
+  FlashSwap {
+    amount: u64,
+    limit: u64,
+    // Callback instruction data
+    data: Vec<u8>,
+  },
+  ClaimTax,
+  InitializeDistribution {
+    // Basis points of each recipient
+    shares: Vec<u16>,
+  },
+  UpdateDistribution {
+    // Basis points of each recipient
+    shares: Vec<u16>,
+  },
+  DistributeTax,
+  InitializeFeeTiers {
+    // (threshold, discount) of each tier
+    tiers: Vec<(u64, u64)>,
+  },
+  UpdateFeeTiers {
+    // (threshold, discount) of each tier
+    tiers: Vec<(u64, u64)>,
+  },
+  SetFeeTiers,
+  InitializeAllowlist {
+    discount: u64,
+  },
+  UpdateAllowlist {
+    discount: u64,
+    // Number of members (the multisig signers follow them)
+    members: u8,
+  },
+  SetAllowlist,
+  MigratePool,
+  InitializeReferrers,
+  UpdateReferrers {
+    // Number of members (the multisig signers follow them)
+    members: u8,
+  },
+  SetReferrers,
+  // Route with a referral, the referral ratio ahead of the hop limits
+  RouteWithReferral {
+    amount: u64,
+    limit: u64,
+    // Share of the fee to the referrer, paid on the last hop
+    referral_ratio: u64,
+    // Minimum output of each hop (none, or one per hop)
+    hop_limits: Vec<u64>,
+  },
+  Stake {
+    amount: u64,
+  },
+  Unstake {
+    amount: u64,
+  },
 }
 
 impl AppInstruction {
@@ -105,10 +260,25 @@ impl AppInstruction {
           .and_then(|slice| slice.try_into().ok())
           .map(u64::from_le_bytes)
           .ok_or(AppError::InvalidInstruction)?;
-        Self::Swap { amount, limit }
+        let referral_ratio = rest
+          .get(16..24)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .unwrap_or(0);
+        Self::Swap {
+          amount,
+          limit,
+          referral_ratio,
+        }
+      }
+      4 => {
+        let mask = unpack_mask(rest)?;
+        Self::FreezePool { mask }
+      }
+      5 => {
+        let mask = unpack_mask(rest)?;
+        Self::ThawPool { mask }
       }
-      4 => Self::FreezePool,
-      5 => Self::ThawPool,
       6 => Self::TransferTaxman,
       7 => Self::TransferOwnership,
       8 => {
@@ -122,7 +292,13 @@ impl AppInstruction {
           .and_then(|slice| slice.try_into().ok())
           .map(u64::from_le_bytes)
           .ok_or(AppError::InvalidInstruction)?;
-        Self::Route { amount, limit }
+        // Optional per-hop minimum outputs
+        let hop_limits = rest.get(16..).map(unpack_u64s).unwrap_or(Ok(vec![]))?;
+        Self::Route {
+          amount,
+          limit,
+          hop_limits,
+        }
       }
       9 => {
         let fee_ratio = rest
@@ -135,9 +311,14 @@ impl AppInstruction {
           .and_then(|slice| slice.try_into().ok())
           .map(u64::from_le_bytes)
           .ok_or(AppError::InvalidInstruction)?;
+        let max_referral_ratio = rest
+          .get(16..24)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes);
         Self::UpdateFee {
           fee_ratio,
           tax_ratio,
+          max_referral_ratio,
         }
       }
       10 => {
@@ -153,6 +334,177 @@ impl AppInstruction {
           .ok_or(AppError::InvalidInstruction)?;
         Self::AddSidedLiquidity { delta_a, delta_b }
       }
+      11 => Self::TransferPauser,
+      12 => Self::TransferFeeManager,
+      13 => {
+        let m = rest
+          .get(..1)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u8::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::InitializeMultisig { m }
+      }
+      14 => {
+        let lpt = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::EmergencyWithdraw { lpt }
+      }
+      15 => Self::Sync,
+      16 => Self::Skim,
+      17 => Self::GetDrift,
+      18 => Self::ClosePool,
+      19 => {
+        let limit = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let paths_data = rest.get(8..).ok_or(AppError::InvalidInstruction)?;
+        if paths_data.len() % 9 != 0 {
+          return Err(AppError::InvalidInstruction.into());
+        }
+        let paths = paths_data
+          .chunks(9)
+          .map(|slice| {
+            let amount = slice
+              .get(..8)
+              .and_then(|slice| slice.try_into().ok())
+              .map(u64::from_le_bytes)
+              .ok_or(AppError::InvalidInstruction)?;
+            let hops = *slice.get(8).ok_or(AppError::InvalidInstruction)?;
+            Ok((amount, hops))
+          })
+          .collect::<Result<_, AppError>>()?;
+        Self::SplitRoute { limit, paths }
+      }
+      20 => {
+        let amount_a = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let amount_b = rest
+          .get(8..16)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let data = rest.get(16..).ok_or(AppError::InvalidInstruction)?.to_vec();
+        Self::FlashLoan {
+          amount_a,
+          amount_b,
+          data,
+        }
+      }
+      21 => {
+        let amount = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let limit = rest
+          .get(8..16)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let data = rest.get(16..).ok_or(AppError::InvalidInstruction)?.to_vec();
+        Self::FlashSwap {
+          amount,
+          limit,
+          data,
+        }
+      }
+      22 => Self::ClaimTax,
+      23 => Self::InitializeDistribution {
+        shares: unpack_u16s(rest)?,
+      },
+      24 => Self::UpdateDistribution {
+        shares: unpack_u16s(rest)?,
+      },
+      25 => Self::DistributeTax,
+      26 => Self::InitializeFeeTiers {
+        tiers: unpack_u64_pairs(rest)?,
+      },
+      27 => Self::UpdateFeeTiers {
+        tiers: unpack_u64_pairs(rest)?,
+      },
+      28 => Self::SetFeeTiers,
+      29 => {
+        let discount = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::InitializeAllowlist { discount }
+      }
+      30 => {
+        let discount = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let members = rest
+          .get(8..9)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u8::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::UpdateAllowlist { discount, members }
+      }
+      31 => Self::SetAllowlist,
+      32 => Self::MigratePool,
+      33 => Self::InitializeReferrers,
+      34 => {
+        let members = rest
+          .get(..1)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u8::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::UpdateReferrers { members }
+      }
+      35 => Self::SetReferrers,
+      36 => {
+        let amount = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let limit = rest
+          .get(8..16)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        let referral_ratio = rest
+          .get(16..24)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        // Optional per-hop minimum outputs
+        let hop_limits = rest.get(24..).map(unpack_u64s).unwrap_or(Ok(vec![]))?;
+        Self::RouteWithReferral {
+          amount,
+          limit,
+          referral_ratio,
+          hop_limits,
+        }
+      }
+      37 => {
+        let amount = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::Stake { amount }
+      }
+      38 => {
+        let amount = rest
+          .get(..8)
+          .and_then(|slice| slice.try_into().ok())
+          .map(u64::from_le_bytes)
+          .ok_or(AppError::InvalidInstruction)?;
+        Self::Unstake { amount }
+      }
       _ => return Err(AppError::InvalidInstruction.into()),
     })
   }
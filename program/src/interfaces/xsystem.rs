@@ -48,4 +48,29 @@ impl XSystem {
     )?;
     Ok(())
   }
+  ///
+  /// Resize a program-owned account, topping it up to stay rent exempt
+  ///
+  pub fn realloc_account<'a>(
+    space: usize,
+    target_acc: &AccountInfo<'a>,
+    payer_acc: &AccountInfo<'a>,
+    sysvar_rent_acc: &AccountInfo<'a>,
+    system_acc: &AccountInfo<'a>,
+  ) -> ProgramResult {
+    let rent = &Rent::from_account_info(sysvar_rent_acc)?;
+    let required_lamports = rent
+      .minimum_balance(space)
+      .saturating_sub(target_acc.lamports());
+
+    if required_lamports > 0 {
+      invoke(
+        &system_instruction::transfer(payer_acc.key, target_acc.key, required_lamports),
+        &[payer_acc.clone(), target_acc.clone(), system_acc.clone()],
+      )?;
+    }
+
+    target_acc.realloc(space, true)?;
+    Ok(())
+  }
 }
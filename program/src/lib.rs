@@ -6,4 +6,6 @@ pub mod helper;
 pub mod instruction;
 pub mod interfaces;
 pub mod processor;
+#[cfg(not(any(target_arch = "bpf", target_os = "solana")))]
+pub mod router;
 pub mod schema;
@@ -2,14 +2,19 @@ use crate::error::AppError;
 use crate::helper::util;
 use crate::interfaces::xsplt::XSPLT;
 use crate::schema::{
-  pool::Pool,
+  pool_state::PAUSE_DEPOSIT,
   pool_trait::{Exchange, Operation},
+  pool_view::PoolView,
 };
+use arrayref::mut_array_refs;
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
+  msg,
+  program::set_return_data,
   program_error::ProgramError,
   program_pack::Pack,
   pubkey::Pubkey,
+  sysvar::{clock::Clock, Sysvar},
 };
 use spl_token::state::Mint;
 use std::result::Result;
@@ -19,7 +24,7 @@ pub fn exec(
   delta_b: u64,
   program_id: &Pubkey,
   accounts: &[AccountInfo],
-) -> Result<(u64, u64, u64), ProgramError> {
+) -> Result<(u64, u64, u64, u64, u64), ProgramError> {
   let accounts_iter = &mut accounts.iter();
   let owner = next_account_info(accounts_iter)?;
   let pool_acc = next_account_info(accounts_iter)?;
@@ -43,16 +48,18 @@ pub fn exec(
   util::is_program(program_id, &[pool_acc])?;
   util::is_signer(&[owner])?;
 
-  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  let pool_acc_data = pool_acc.data.borrow();
+  let pool_view = PoolView::load(&pool_acc_data)?;
+  pool_view.is_unlocked()?;
   let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
-  if pool_data.is_frozen() {
+  if pool_view.is_paused(PAUSE_DEPOSIT) {
     return Err(AppError::FrozenPool.into());
   }
-  if pool_data.mint_lpt != *mint_lpt_acc.key
-    || pool_data.mint_a != *mint_a_acc.key
-    || pool_data.mint_b != *mint_b_acc.key
-    || pool_data.treasury_a != *treasury_a_acc.key
-    || pool_data.treasury_b != *treasury_b_acc.key
+  if pool_view.mint_lpt != mint_lpt_acc.key.to_bytes()
+    || pool_view.mint_a != mint_a_acc.key.to_bytes()
+    || pool_view.mint_b != mint_b_acc.key.to_bytes()
+    || pool_view.treasury_a != treasury_a_acc.key.to_bytes()
+    || pool_view.treasury_b != treasury_b_acc.key.to_bytes()
   {
     return Err(AppError::UnmatchedPool.into());
   }
@@ -62,19 +69,18 @@ pub fn exec(
 
   // Balance the deposit
   let mint_lpt_data = Mint::unpack(&mint_lpt_acc.data.borrow())?;
-  let (a_star, b_star, lpt, reserve_a, reserve_b, _) = pool_data
+  let (a_star, b_star, lpt, reserve_a, reserve_b, _) = pool_view
     .deposit(delta_a, delta_b, mint_lpt_data.supply)
     .ok_or(AppError::Overflow)?;
+  drop(pool_acc_data);
   let a_remainer = delta_a.checked_sub(a_star).ok_or(AppError::Overflow)?;
   let b_remainer = delta_b.checked_sub(b_star).ok_or(AppError::Overflow)?;
+  // Lock the pool
+  PoolView::load_mut(&mut pool_acc.data.borrow_mut())?.lock();
   // Deposit token A
   XSPLT::transfer(a_star, src_a_acc, treasury_a_acc, owner, splt_program, &[])?;
-  pool_data.reserve_a = reserve_a;
   // Deposit token B
   XSPLT::transfer(b_star, src_b_acc, treasury_b_acc, owner, splt_program, &[])?;
-  pool_data.reserve_b = reserve_b;
-  // Update pool
-  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
   // Initialize lpt account
   util::checked_initialize_splt_account(
     owner,
@@ -88,6 +94,36 @@ pub fn exec(
   )?;
   // Mint LPT
   XSPLT::mint_to(lpt, mint_lpt_acc, lpt_acc, treasurer, splt_program, seed)?;
+  // Update pool
+  let mut pool_acc_data = pool_acc.data.borrow_mut();
+  let pool_view = PoolView::load_mut(&mut pool_acc_data)?;
+  pool_view.set_reserve(0, reserve_a)?;
+  pool_view.set_reserve(1, reserve_b)?;
+  let mut stats = pool_view.stats()?;
+  stats.record_liquidity(Clock::get()?.slot);
+  pool_view.set_stats(&stats);
+  pool_view.unlock();
+
+  Ok((a_star, b_star, lpt, a_remainer, b_remainer))
+}
 
-  Ok((lpt, a_remainer, b_remainer))
+///
+/// Report the consumed amounts and the minted LP of a top-level deposit (the remainers
+/// are never pulled). The return data is (a, b, lpt) as little-endian u64s.
+///
+pub fn report(a_star: u64, b_star: u64, lpt: u64, a_remainer: u64, b_remainer: u64) {
+  msg!(
+    "AddLiquidity: deposited {} {}, minted {}, unused {} {}",
+    a_star,
+    b_star,
+    lpt,
+    a_remainer,
+    b_remainer
+  );
+  let mut data = [0u8; 24];
+  let (a_dst, b_dst, lpt_dst) = mut_array_refs![&mut data, 8, 8, 8];
+  *a_dst = a_star.to_le_bytes();
+  *b_dst = b_star.to_le_bytes();
+  *lpt_dst = lpt.to_le_bytes();
+  set_return_data(&data);
 }
@@ -1,56 +1,90 @@
 use crate::error::AppError;
+use crate::helper::math::{U128Roots, PRECISION};
 use crate::processor::{add_liquidity, swap};
-use crate::schema::{pool::Pool, pool_trait::Exchange};
+use crate::schema::{pool_trait::Exchange, pool_view::PoolView};
 use num_traits::ToPrimitive;
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
   program_error::ProgramError,
-  program_pack::Pack,
   pubkey::Pubkey,
 };
 use std::result::Result;
 
+///
+/// The amount of bid to swap so that the rest of the bid and the received ask
+/// match the pool ratio after the swap. With k = (1 - fee)(1 - tax), it is the
+/// positive root of (k + fee)s^2 + ((1 + k)reserve - fee*amount)s - amount*reserve = 0
+///
+pub fn split(amount: u64, reserve: u64, fee_ratio: u64, tax_ratio: u64) -> Option<u64> {
+  if reserve == 0 {
+    return None;
+  }
+  let precision = PRECISION.to_u128()?;
+  let fee_ratio = fee_ratio.to_u128()?;
+  let tax_ratio = tax_ratio.to_u128()?;
+  let k = precision
+    .checked_sub(fee_ratio)?
+    .checked_mul(precision.checked_sub(tax_ratio)?)?
+    .checked_div(precision)?;
+  let quadratic = k.checked_add(fee_ratio)?;
+  let linear = k.checked_add(precision)?;
+  let a = amount.to_u128()?;
+  let r = reserve.to_u128()?;
+  let b_pos = linear.checked_mul(r)?;
+  let b_neg = fee_ratio.checked_mul(a)?;
+  let b = if b_pos > b_neg {
+    b_pos - b_neg
+  } else {
+    b_neg - b_pos
+  };
+  let root = discriminant_root(b, quadratic.checked_mul(precision)?.checked_mul(4)?, a * r)?;
+  let s = if b_pos > b_neg {
+    // Rationalized to 2*precision*amount*reserve / (b + root), which does not cancel
+    // for a small amount against a large reserve
+    let numerator = precision.checked_mul(2)?.checked_mul(r)?;
+    let denominator = b.checked_add(root)?;
+    // Scale the ratio down to 64 bits so that its product with amount fits u128
+    let shift = (128 - denominator.leading_zeros()).saturating_sub(64);
+    a.checked_mul(numerator >> shift)?
+      .checked_div(denominator >> shift)?
+  } else {
+    root
+      .checked_add(b)?
+      .checked_div(quadratic.checked_mul(2)?)?
+  };
+  Some(s.to_u64()?.min(amount))
+}
+
+///
+/// sqrt(b^2 + c*d), which can take up to 190 bits. The operands are scaled down
+/// only as far as u128 requires, and the root scaled back up.
+///
+fn discriminant_root(b: u128, c: u128, d: u128) -> Option<u128> {
+  for shift in 0..64 {
+    let b = b >> shift;
+    let d = d.checked_shr(2 * shift).unwrap_or(0);
+    if let Some(discriminant) = b
+      .checked_mul(b)
+      .and_then(|square| square.checked_add(c.checked_mul(d)?))
+    {
+      return discriminant.sqrt().checked_shl(shift);
+    }
+  }
+  None
+}
+
 pub fn rake(
   amount: u64,
   bid_mint: &Pubkey,
   ask_mint: &Pubkey,
   pool_acc: &AccountInfo,
 ) -> Option<u64> {
-  let pool_data = Pool::unpack(&pool_acc.data.borrow()).ok()?;
-  let mut delta = amount;
-  let mut bid_amount = amount.checked_div(2)?;
-  loop {
-    // Simulate a swap
-    let (temp_ask_amount, new_bid_reserve, temp_new_ask_reserve) =
-      pool_data.curve(bid_amount, bid_mint, ask_mint)?;
-    let (ask_amount, fee, _) = pool_data.fee(temp_ask_amount)?;
-    let new_ask_reserve = temp_new_ask_reserve.checked_add(fee)?;
-    // Compute updated step
-    let remainer = amount.checked_sub(bid_amount)?;
-    let expected_remainer = ask_amount
-      .to_u128()?
-      .checked_mul(new_bid_reserve.to_u128()?)?
-      .checked_div(new_ask_reserve.to_u128()?)?
-      .to_u64()?;
-    let next_delta = if remainer > expected_remainer {
-      remainer.checked_sub(expected_remainer)?.checked_div(2)?
-    } else {
-      expected_remainer.checked_sub(remainer)?.checked_div(2)?
-    };
-    // Stop condition
-    if delta > next_delta {
-      delta = next_delta;
-    } else {
-      break;
-    }
-    // Converge bid amount
-    bid_amount = if remainer > expected_remainer {
-      bid_amount.checked_add(delta)?
-    } else {
-      bid_amount.checked_sub(delta)?
-    };
-  }
-  Some(bid_amount)
+  let pool_acc_data = pool_acc.data.borrow();
+  let pool_view = PoolView::load(&pool_acc_data).ok()?;
+  let (_, bid_reserve) = pool_view.get_reserve(bid_mint)?;
+  pool_view.get_reserve(ask_mint)?;
+  let (fee_ratio, tax_ratio, _) = pool_view.ratios();
+  split(amount, bid_reserve, fee_ratio, tax_ratio)
 }
 
 pub fn exec(
@@ -73,10 +107,6 @@ pub fn exec(
   let mint_b_acc = next_account_info(accounts_iter)?;
   let treasury_b_acc = next_account_info(accounts_iter)?;
 
-  let taxman_acc = next_account_info(accounts_iter)?;
-  let treasury_taxman_a_acc = next_account_info(accounts_iter)?;
-  let treasury_taxman_b_acc = next_account_info(accounts_iter)?;
-
   let treasurer = next_account_info(accounts_iter)?;
   let system_program = next_account_info(accounts_iter)?;
   let splt_program = next_account_info(accounts_iter)?;
@@ -102,16 +132,20 @@ pub fn exec(
     splata_program.clone(),
   ];
   // Deposit first
-  let (unraked_lpt, a_remainer, b_remainer) =
+  let (_, _, unraked_lpt, a_remainer, b_remainer) =
     add_liquidity::exec(delta_a, delta_b, program_id, &deposit_accounts)?;
   // Handle the remainer of A
   if a_remainer > 0 {
     let bid_amount =
       rake(a_remainer, mint_a_acc.key, mint_b_acc.key, pool_acc).ok_or(AppError::Overflow)?;
+    // Too small to be worth a swap
+    if bid_amount == 0 {
+      return Ok(unraked_lpt);
+    }
     let a = a_remainer
       .checked_sub(bid_amount)
       .ok_or(AppError::Overflow)?;
-    let swap_accounts: [AccountInfo; 15] = [
+    let swap_accounts: [AccountInfo; 13] = [
       owner.clone(),
       pool_acc.clone(),
       src_a_acc.clone(),
@@ -120,16 +154,14 @@ pub fn exec(
       src_b_acc.clone(),
       mint_b_acc.clone(),
       treasury_b_acc.clone(),
-      taxman_acc.clone(),
-      treasury_taxman_b_acc.clone(),
       treasurer.clone(),
       system_program.clone(),
       splt_program.clone(),
       sysvar_rent_acc.clone(),
       splata_program.clone(),
     ];
-    let b = swap::exec(bid_amount, 0, program_id, &swap_accounts)?;
-    let (raked_lpt, _, _) = add_liquidity::exec(a, b, program_id, &deposit_accounts)?;
+    let b = swap::exec(bid_amount, 0, 0, program_id, &swap_accounts)?;
+    let (_, _, raked_lpt, _, _) = add_liquidity::exec(a, b, program_id, &deposit_accounts)?;
     return Ok(
       unraked_lpt
         .checked_add(raked_lpt)
@@ -140,10 +172,14 @@ pub fn exec(
   if b_remainer > 0 {
     let bid_amount =
       rake(b_remainer, mint_b_acc.key, mint_a_acc.key, pool_acc).ok_or(AppError::Overflow)?;
+    // Too small to be worth a swap
+    if bid_amount == 0 {
+      return Ok(unraked_lpt);
+    }
     let b = b_remainer
       .checked_sub(bid_amount)
       .ok_or(AppError::Overflow)?;
-    let swap_accounts: [AccountInfo; 15] = [
+    let swap_accounts: [AccountInfo; 13] = [
       owner.clone(),
       pool_acc.clone(),
       src_b_acc.clone(),
@@ -152,16 +188,14 @@ pub fn exec(
       src_a_acc.clone(),
       mint_a_acc.clone(),
       treasury_a_acc.clone(),
-      taxman_acc.clone(),
-      treasury_taxman_a_acc.clone(),
       treasurer.clone(),
       system_program.clone(),
       splt_program.clone(),
       sysvar_rent_acc.clone(),
       splata_program.clone(),
     ];
-    let a = swap::exec(bid_amount, 0, program_id, &swap_accounts)?;
-    let (raked_lpt, _, _) = add_liquidity::exec(a, b, program_id, &deposit_accounts)?;
+    let a = swap::exec(bid_amount, 0, 0, program_id, &swap_accounts)?;
+    let (_, _, raked_lpt, _, _) = add_liquidity::exec(a, b, program_id, &deposit_accounts)?;
     return Ok(
       unraked_lpt
         .checked_add(raked_lpt)
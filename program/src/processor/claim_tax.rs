@@ -0,0 +1,109 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::{distribution::Distribution, pool::Pool, pool_trait::Operation};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+///
+/// Pay the accrued tax of both tokens out to the taxman.
+/// The payer funds the missing taxman accounts, so a multisig taxman works too.
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(u64, u64), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let taxman = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+
+  let dst_a_acc = next_account_info(accounts_iter)?;
+  let mint_a_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+
+  let dst_b_acc = next_account_info(accounts_iter)?;
+  let mint_b_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_signer(&[payer])?;
+  util::is_authority(program_id, taxman, signers)?;
+  // A distribution taxman is paid out by DistributeTax only
+  if taxman.owner == program_id && taxman.data_len() == Distribution::LEN {
+    return Err(AppError::InvalidDistribution.into());
+  }
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
+  if pool_data.taxman != *taxman.key {
+    return Err(AppError::InvalidOwner.into());
+  }
+  if pool_data.mint_a != *mint_a_acc.key
+    || pool_data.mint_b != *mint_b_acc.key
+    || pool_data.treasury_a != *treasury_a_acc.key
+    || pool_data.treasury_b != *treasury_b_acc.key
+  {
+    return Err(AppError::UnmatchedPool.into());
+  }
+
+  let tax_a = pool_data.tax_a;
+  let tax_b = pool_data.tax_b;
+  // Lock the pool and clear the counters
+  pool_data.tax_a = 0;
+  pool_data.tax_b = 0;
+  pool_data.lock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  // Claim A (Initialize the taxman account if not exsting)
+  if tax_a != 0 {
+    util::checked_transfer_splt(
+      tax_a,
+      payer,
+      treasury_a_acc,
+      treasurer,
+      dst_a_acc,
+      taxman,
+      mint_a_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  // Claim B (Initialize the taxman account if not exsting)
+  if tax_b != 0 {
+    util::checked_transfer_splt(
+      tax_b,
+      payer,
+      treasury_b_acc,
+      treasurer,
+      dst_b_acc,
+      taxman,
+      mint_b_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  msg!("ClaimTax: {} {}", tax_a, tax_b);
+
+  // Unlock the pool
+  pool_data.unlock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok((tax_a, tax_b))
+}
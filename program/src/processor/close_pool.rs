@@ -0,0 +1,118 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsplt::XSPLT;
+use crate::schema::{pool::Pool, pool_trait::Operation};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use spl_token::state::{Account, Mint};
+use std::result::Result;
+
+///
+/// Sweep and close the treasuries of an emptied pool, then reclaim its rent.
+/// The payer funds the missing recipient accounts, so a multisig owner works too.
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let owner = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+  let mint_lpt_acc = next_account_info(accounts_iter)?;
+
+  let dst_a_acc = next_account_info(accounts_iter)?;
+  let mint_a_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+
+  let dst_b_acc = next_account_info(accounts_iter)?;
+  let mint_b_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  let recipient = next_account_info(accounts_iter)?;
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_signer(&[payer])?;
+  util::is_authority(program_id, owner, signers)?;
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
+  pool_data.is_owner(*owner.key)?;
+  if pool_data.mint_lpt != *mint_lpt_acc.key
+    || pool_data.mint_a != *mint_a_acc.key
+    || pool_data.mint_b != *mint_b_acc.key
+    || pool_data.treasury_a != *treasury_a_acc.key
+    || pool_data.treasury_b != *treasury_b_acc.key
+  {
+    return Err(AppError::UnmatchedPool.into());
+  }
+  let mint_lpt_data = Mint::unpack(&mint_lpt_acc.data.borrow())?;
+  // The accrued tax must be claimed before the treasuries are swept
+  if mint_lpt_data.supply != 0 || pool_data.tax_a != 0 || pool_data.tax_b != 0 {
+    return Err(AppError::NonEmptyPool.into());
+  }
+
+  // Lock the pool
+  pool_data.lock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  // Sweep the dust and close treasury A
+  let treasury_a_data = Account::unpack(&treasury_a_acc.data.borrow())?;
+  if treasury_a_data.amount != 0 {
+    util::checked_transfer_splt(
+      treasury_a_data.amount,
+      payer,
+      treasury_a_acc,
+      treasurer,
+      dst_a_acc,
+      recipient,
+      mint_a_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  XSPLT::close_account(treasury_a_acc, recipient, treasurer, splt_program, seed)?;
+  // Sweep the dust and close treasury B
+  let treasury_b_data = Account::unpack(&treasury_b_acc.data.borrow())?;
+  if treasury_b_data.amount != 0 {
+    util::checked_transfer_splt(
+      treasury_b_data.amount,
+      payer,
+      treasury_b_acc,
+      treasurer,
+      dst_b_acc,
+      recipient,
+      mint_b_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  XSPLT::close_account(treasury_b_acc, recipient, treasurer, splt_program, seed)?;
+  // Zero the pool data
+  for byte in pool_acc.data.borrow_mut().iter_mut() {
+    *byte = 0;
+  }
+  // Return the pool lamports
+  let lamports = pool_acc.lamports();
+  **recipient.lamports.borrow_mut() = recipient
+    .lamports()
+    .checked_add(lamports)
+    .ok_or(AppError::Overflow)?;
+  **pool_acc.lamports.borrow_mut() = 0;
+
+  Ok(())
+}
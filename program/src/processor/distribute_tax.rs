@@ -0,0 +1,122 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::{distribution::Distribution, pool::Pool, pool_trait::Operation};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::result::Result;
+
+///
+/// Split the accrued tax of a pool whose taxman is a distribution account.
+/// Anyone can crank it, the payer only funds the missing recipient accounts.
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(u64, u64), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+
+  let mint_a_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+
+  let mint_b_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  let distribution_acc = next_account_info(accounts_iter)?;
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+  // The rest are the (recipient, dst_a, dst_b) of each recipient
+  let recipient_accounts = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc, distribution_acc])?;
+  util::is_signer(&[payer])?;
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
+  if pool_data.taxman != *distribution_acc.key
+    || pool_data.mint_a != *mint_a_acc.key
+    || pool_data.mint_b != *mint_b_acc.key
+    || pool_data.treasury_a != *treasury_a_acc.key
+    || pool_data.treasury_b != *treasury_b_acc.key
+  {
+    return Err(AppError::UnmatchedPool.into());
+  }
+  let distribution_data = Distribution::unpack(&distribution_acc.data.borrow())?;
+  let n = distribution_data.n as usize;
+  if recipient_accounts.len() != 3 * n {
+    return Err(AppError::InvalidDistribution.into());
+  }
+  // The crank is permissionless so the payouts must land in the recipients' associated accounts
+  for (chunk, recipient) in recipient_accounts
+    .chunks(3)
+    .zip(distribution_data.recipients[..n].iter())
+  {
+    if *chunk[0].key != *recipient
+      || *chunk[1].key != get_associated_token_address(recipient, mint_a_acc.key)
+      || *chunk[2].key != get_associated_token_address(recipient, mint_b_acc.key)
+    {
+      return Err(AppError::InvalidDistribution.into());
+    }
+  }
+
+  let tax_a = pool_data.tax_a;
+  let tax_b = pool_data.tax_b;
+  let amounts_a = distribution_data.split(tax_a).ok_or(AppError::Overflow)?;
+  let amounts_b = distribution_data.split(tax_b).ok_or(AppError::Overflow)?;
+  // Lock the pool and clear the counters
+  pool_data.tax_a = 0;
+  pool_data.tax_b = 0;
+  pool_data.lock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  // Pay each recipient (Initialize the recipient accounts if not exsting)
+  for (i, chunk) in recipient_accounts.chunks(3).enumerate() {
+    let (recipient_acc, dst_a_acc, dst_b_acc) = (&chunk[0], &chunk[1], &chunk[2]);
+    if amounts_a[i] != 0 {
+      util::checked_transfer_splt(
+        amounts_a[i],
+        payer,
+        treasury_a_acc,
+        treasurer,
+        dst_a_acc,
+        recipient_acc,
+        mint_a_acc,
+        system_program,
+        splt_program,
+        sysvar_rent_acc,
+        splata_program,
+        seed,
+      )?;
+    }
+    if amounts_b[i] != 0 {
+      util::checked_transfer_splt(
+        amounts_b[i],
+        payer,
+        treasury_b_acc,
+        treasurer,
+        dst_b_acc,
+        recipient_acc,
+        mint_b_acc,
+        system_program,
+        splt_program,
+        sysvar_rent_acc,
+        splata_program,
+        seed,
+      )?;
+    }
+  }
+  msg!("DistributeTax: {} {}", tax_a, tax_b);
+
+  // Unlock the pool
+  pool_data.unlock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok((tax_a, tax_b))
+}
@@ -0,0 +1,154 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::{
+  pool::Pool,
+  pool_state::PAUSE_SWAP,
+  pool_trait::{Exchange, Operation},
+};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  instruction::{AccountMeta, Instruction},
+  program::invoke,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+  sysvar::{clock::Clock, Sysvar},
+};
+use spl_token::state::Account;
+use std::result::Result;
+
+pub fn exec(
+  amount_a: u64,
+  amount_b: u64,
+  data: &[u8],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(u64, u64), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let owner = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+
+  let dst_a_acc = next_account_info(accounts_iter)?;
+  let mint_a_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+
+  let dst_b_acc = next_account_info(accounts_iter)?;
+  let mint_b_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+
+  let callback_program = next_account_info(accounts_iter)?;
+  // The rest are the callback accounts
+  let callback_accounts = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_signer(&[owner])?;
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
+  if pool_data.is_paused(PAUSE_SWAP) {
+    return Err(AppError::FrozenPool.into());
+  }
+  if pool_data.mint_a != *mint_a_acc.key
+    || pool_data.mint_b != *mint_b_acc.key
+    || pool_data.treasury_a != *treasury_a_acc.key
+    || pool_data.treasury_b != *treasury_b_acc.key
+  {
+    return Err(AppError::UnmatchedPool.into());
+  }
+  if amount_a == 0 && amount_b == 0 {
+    return Err(AppError::ZeroValue.into());
+  }
+
+  // Expected balances after repayment
+  let fee_a = pool_data.flash_fee(amount_a).ok_or(AppError::Overflow)?;
+  let fee_b = pool_data.flash_fee(amount_b).ok_or(AppError::Overflow)?;
+  let expected_a = Account::unpack(&treasury_a_acc.data.borrow())?
+    .amount
+    .checked_add(fee_a)
+    .ok_or(AppError::Overflow)?;
+  let expected_b = Account::unpack(&treasury_b_acc.data.borrow())?
+    .amount
+    .checked_add(fee_b)
+    .ok_or(AppError::Overflow)?;
+  // Lock the pool
+  pool_data.lock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  // Lend A
+  if amount_a != 0 {
+    util::checked_transfer_splt(
+      amount_a,
+      owner,
+      treasury_a_acc,
+      treasurer,
+      dst_a_acc,
+      owner,
+      mint_a_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  // Lend B
+  if amount_b != 0 {
+    util::checked_transfer_splt(
+      amount_b,
+      owner,
+      treasury_b_acc,
+      treasurer,
+      dst_b_acc,
+      owner,
+      mint_b_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  // Call back the borrower
+  let ix = Instruction {
+    program_id: *callback_program.key,
+    accounts: callback_accounts
+      .iter()
+      .map(|acc| AccountMeta {
+        pubkey: *acc.key,
+        is_signer: acc.is_signer,
+        is_writable: acc.is_writable,
+      })
+      .collect(),
+    data: data.to_vec(),
+  };
+  let mut callback_infos = callback_accounts.to_vec();
+  callback_infos.push(callback_program.clone());
+  invoke(&ix, &callback_infos)?;
+  // Verify the repayment
+  let treasury_a_data = Account::unpack(&treasury_a_acc.data.borrow())?;
+  let treasury_b_data = Account::unpack(&treasury_b_acc.data.borrow())?;
+  if treasury_a_data.amount < expected_a || treasury_b_data.amount < expected_b {
+    return Err(AppError::UnpaidFlashLoan.into());
+  }
+  // Add the fees to the reserves and unlock the pool
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.reserve_a = pool_data
+    .reserve_a
+    .checked_add(fee_a)
+    .ok_or(AppError::Overflow)?;
+  pool_data.reserve_b = pool_data
+    .reserve_b
+    .checked_add(fee_b)
+    .ok_or(AppError::Overflow)?;
+  pool_data.stats.record_fee(fee_a, fee_b, Clock::get()?.slot);
+  pool_data.unlock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok((fee_a, fee_b))
+}
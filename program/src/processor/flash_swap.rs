@@ -0,0 +1,179 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::{
+  pool::Pool,
+  pool_state::PAUSE_SWAP,
+  pool_trait::{Exchange, Operation},
+};
+use num_traits::ToPrimitive;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  instruction::{AccountMeta, Instruction},
+  program::invoke,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+  sysvar::{clock::Clock, Sysvar},
+};
+use spl_token::state::Account;
+use std::result::Result;
+
+///
+/// Optimistically send the ask, call back the borrower,
+/// then require the bid in the treasury
+///
+pub fn exec(
+  amount: u64,
+  limit: u64,
+  data: &[u8],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<u64, ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let owner = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+
+  let mint_bid_acc = next_account_info(accounts_iter)?;
+  let treasury_bid_acc = next_account_info(accounts_iter)?;
+
+  let dst_ask_acc = next_account_info(accounts_iter)?;
+  let mint_ask_acc = next_account_info(accounts_iter)?;
+  let treasury_ask_acc = next_account_info(accounts_iter)?;
+
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+
+  let callback_program = next_account_info(accounts_iter)?;
+  // The rest are the callback accounts
+  let callback_accounts = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_signer(&[owner])?;
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
+  if pool_data.is_paused(PAUSE_SWAP) {
+    return Err(AppError::FrozenPool.into());
+  }
+  if *mint_bid_acc.key == *mint_ask_acc.key {
+    return Err(AppError::SameMint.into());
+  }
+  let (bid_code, bid_reserve) = pool_data
+    .get_reserve(mint_bid_acc.key)
+    .ok_or(AppError::UnmatchedPool)?;
+  let (ask_code, ask_reserve) = pool_data
+    .get_reserve(mint_ask_acc.key)
+    .ok_or(AppError::UnmatchedPool)?;
+  let (treasury_bid, treasury_ask) = match bid_code {
+    0 => (pool_data.treasury_a, pool_data.treasury_b),
+    _ => (pool_data.treasury_b, pool_data.treasury_a),
+  };
+  if treasury_bid != *treasury_bid_acc.key || treasury_ask != *treasury_ask_acc.key {
+    return Err(AppError::UnmatchedPool.into());
+  }
+  if amount == 0 {
+    return Err(AppError::ZeroValue.into());
+  }
+
+  let bid_amount = amount;
+  let (temp_ask_amount, _, temp_new_ask_reserve) = pool_data
+    .curve(bid_amount, mint_bid_acc.key, mint_ask_acc.key)
+    .ok_or(AppError::Overflow)?;
+  let (ask_amount, fee, tax) = pool_data.fee(temp_ask_amount).ok_or(AppError::Overflow)?;
+  let new_ask_reserve = temp_new_ask_reserve
+    .checked_add(fee)
+    .ok_or(AppError::Overflow)?;
+
+  if ask_amount < limit {
+    return Err(AppError::ExceedLimit.into());
+  }
+
+  // Bid balance before the callback
+  let prev_bid_balance = Account::unpack(&treasury_bid_acc.data.borrow())?.amount;
+  // Lock the pool
+  pool_data.lock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  // Execute ask optimistically (Initialize ask account if not exsting)
+  util::checked_transfer_splt(
+    ask_amount,
+    owner,
+    treasury_ask_acc,
+    treasurer,
+    dst_ask_acc,
+    owner,
+    mint_ask_acc,
+    system_program,
+    splt_program,
+    sysvar_rent_acc,
+    splata_program,
+    seed,
+  )?;
+  // Call back the borrower
+  let ix = Instruction {
+    program_id: *callback_program.key,
+    accounts: callback_accounts
+      .iter()
+      .map(|acc| AccountMeta {
+        pubkey: *acc.key,
+        is_signer: acc.is_signer,
+        is_writable: acc.is_writable,
+      })
+      .collect(),
+    data: data.to_vec(),
+  };
+  let mut callback_infos = callback_accounts.to_vec();
+  callback_infos.push(callback_program.clone());
+  invoke(&ix, &callback_infos)?;
+  // Require the bid
+  let treasury_bid_data = Account::unpack(&treasury_bid_acc.data.borrow())?;
+  let received = treasury_bid_data
+    .amount
+    .checked_sub(prev_bid_balance)
+    .ok_or(AppError::UnpaidFlashLoan)?;
+  if received < bid_amount {
+    return Err(AppError::UnpaidFlashLoan.into());
+  }
+  let new_bid_reserve = bid_reserve
+    .checked_add(received)
+    .ok_or(AppError::Overflow)?;
+  // Enforce the invariant
+  let k = bid_reserve
+    .to_u128()
+    .and_then(|reserve| reserve.checked_mul(ask_reserve.to_u128()?))
+    .ok_or(AppError::Overflow)?;
+  let new_k = new_bid_reserve
+    .to_u128()
+    .and_then(|reserve| reserve.checked_mul(new_ask_reserve.to_u128()?))
+    .ok_or(AppError::Overflow)?;
+  if new_k < k {
+    return Err(AppError::UnpaidFlashLoan.into());
+  }
+  // Update the reserves and unlock the pool
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  match bid_code {
+    0 => pool_data.reserve_a = new_bid_reserve,
+    _ => pool_data.reserve_b = new_bid_reserve,
+  }
+  match ask_code {
+    0 => pool_data.reserve_a = new_ask_reserve,
+    _ => pool_data.reserve_b = new_ask_reserve,
+  }
+  pool_data
+    .accrue_tax(mint_ask_acc.key, tax)
+    .ok_or(AppError::Overflow)?;
+  pool_data.stats.record_swap(
+    (bid_code, received),
+    (ask_code, ask_amount),
+    fee,
+    tax,
+    Clock::get()?.slot,
+  );
+  pool_data.unlock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok(ask_amount)
+}
@@ -0,0 +1,50 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::pool::{drift, Pool};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use spl_token::state::Account;
+use std::result::Result;
+
+///
+/// Read-only view (to be simulated) that logs the current drift
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(i128, i128), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let pool_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  util::is_program(program_id, &[pool_acc])?;
+
+  let pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  if pool_data.treasury_a != *treasury_a_acc.key || pool_data.treasury_b != *treasury_b_acc.key {
+    return Err(AppError::UnmatchedPool.into());
+  }
+
+  let treasury_a_data = Account::unpack(&treasury_a_acc.data.borrow())?;
+  let treasury_b_data = Account::unpack(&treasury_b_acc.data.borrow())?;
+  // The accrued tax is held in the treasuries but excluded from the reserves
+  let recorded_a = pool_data
+    .reserve_a
+    .checked_add(pool_data.tax_a)
+    .ok_or(AppError::Overflow)?;
+  let recorded_b = pool_data
+    .reserve_b
+    .checked_add(pool_data.tax_b)
+    .ok_or(AppError::Overflow)?;
+  let (drift_a, drift_b) = drift(
+    treasury_a_data.amount,
+    treasury_b_data.amount,
+    recorded_a,
+    recorded_b,
+  );
+  msg!("Drift: {} {}", drift_a, drift_b);
+
+  Ok((drift_a, drift_b))
+}
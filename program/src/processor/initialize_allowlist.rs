@@ -0,0 +1,52 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::allowlist::Allowlist;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack},
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(
+  discount: u64,
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let allowlist_acc = next_account_info(accounts_iter)?;
+  let authority = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  // The rest are the members
+  let members: Vec<Pubkey> = accounts_iter.map(|member| *member.key).collect();
+
+  util::is_signer(&[payer, allowlist_acc])?;
+
+  // Initialize allowlist account
+  if !XSystem::check_account(allowlist_acc)? {
+    XSystem::rent_account(
+      Allowlist::LEN,
+      allowlist_acc,
+      payer,
+      program_id,
+      sysvar_rent_acc,
+      system_program,
+    )?;
+  }
+  util::is_program(program_id, &[allowlist_acc])?;
+  let mut allowlist_data = Allowlist::unpack_unchecked(&allowlist_acc.data.borrow())?;
+  if allowlist_data.is_initialized() {
+    return Err(AppError::AlreadyInitialized.into());
+  }
+  // Update allowlist data
+  allowlist_data.configure(discount, &members)?;
+  allowlist_data.authority = *authority.key;
+  allowlist_data.is_initialized = true;
+  Allowlist::pack(allowlist_data, &mut allowlist_acc.data.borrow_mut())?;
+
+  Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::distribution::Distribution;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack},
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(
+  shares: &[u16],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let distribution_acc = next_account_info(accounts_iter)?;
+  let authority = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  // The rest are the recipients, in the order of the shares
+  let recipients: Vec<Pubkey> = accounts_iter.map(|recipient| *recipient.key).collect();
+
+  util::is_signer(&[payer, distribution_acc])?;
+
+  // Initialize distribution account
+  if !XSystem::check_account(distribution_acc)? {
+    XSystem::rent_account(
+      Distribution::LEN,
+      distribution_acc,
+      payer,
+      program_id,
+      sysvar_rent_acc,
+      system_program,
+    )?;
+  }
+  util::is_program(program_id, &[distribution_acc])?;
+  let mut distribution_data = Distribution::unpack_unchecked(&distribution_acc.data.borrow())?;
+  if distribution_data.is_initialized() {
+    return Err(AppError::AlreadyInitialized.into());
+  }
+  // Update distribution data
+  distribution_data.configure(&recipients, shares)?;
+  distribution_data.authority = *authority.key;
+  distribution_data.is_initialized = true;
+  Distribution::pack(distribution_data, &mut distribution_acc.data.borrow_mut())?;
+
+  Ok(())
+}
@@ -0,0 +1,52 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::fee_tiers::FeeTiers;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack},
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(
+  tiers: &[(u64, u64)],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let fee_tiers_acc = next_account_info(accounts_iter)?;
+  let authority = next_account_info(accounts_iter)?;
+  let mint_acc = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+
+  util::is_signer(&[payer, fee_tiers_acc])?;
+
+  // Initialize fee tiers account
+  if !XSystem::check_account(fee_tiers_acc)? {
+    XSystem::rent_account(
+      FeeTiers::LEN,
+      fee_tiers_acc,
+      payer,
+      program_id,
+      sysvar_rent_acc,
+      system_program,
+    )?;
+  }
+  util::is_program(program_id, &[fee_tiers_acc])?;
+  let mut fee_tiers_data = FeeTiers::unpack_unchecked(&fee_tiers_acc.data.borrow())?;
+  if fee_tiers_data.is_initialized() {
+    return Err(AppError::AlreadyInitialized.into());
+  }
+  // Update fee tiers data
+  fee_tiers_data.configure(tiers)?;
+  fee_tiers_data.authority = *authority.key;
+  fee_tiers_data.mint = *mint_acc.key;
+  fee_tiers_data.is_initialized = true;
+  FeeTiers::pack(fee_tiers_data, &mut fee_tiers_acc.data.borrow_mut())?;
+
+  Ok(())
+}
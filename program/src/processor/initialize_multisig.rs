@@ -0,0 +1,61 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::multisig::{Multisig, MAX_SIGNERS};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack},
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(m: u8, program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let multisig_acc = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  // The rest are the signer keys
+  let signers = accounts_iter.as_slice();
+
+  util::is_signer(&[payer, multisig_acc])?;
+
+  let n = signers.len();
+  if n == 0 || n > MAX_SIGNERS || m == 0 || m as usize > n {
+    return Err(AppError::InvalidMultisig.into());
+  }
+  // A key registered twice would count twice towards m
+  for (i, signer) in signers.iter().enumerate() {
+    if signers[..i].iter().any(|other| other.key == signer.key) {
+      return Err(AppError::InvalidMultisig.into());
+    }
+  }
+
+  // Initialize multisig account
+  if !XSystem::check_account(multisig_acc)? {
+    XSystem::rent_account(
+      Multisig::LEN,
+      multisig_acc,
+      payer,
+      program_id,
+      sysvar_rent_acc,
+      system_program,
+    )?;
+  }
+  util::is_program(program_id, &[multisig_acc])?;
+  let mut multisig_data = Multisig::unpack_unchecked(&multisig_acc.data.borrow())?;
+  if multisig_data.is_initialized() {
+    return Err(AppError::AlreadyInitialized.into());
+  }
+  // Update multisig data
+  multisig_data.m = m;
+  multisig_data.n = n as u8;
+  multisig_data.is_initialized = true;
+  for (i, signer) in signers.iter().enumerate() {
+    multisig_data.signers[i] = *signer.key;
+  }
+  Multisig::pack(multisig_data, &mut multisig_acc.data.borrow_mut())?;
+
+  Ok(())
+}
@@ -1,7 +1,7 @@
 use crate::error::AppError;
 use crate::helper::{math::U128Roots, pubutil::Boolean, util};
 use crate::interfaces::{xsplt::XSPLT, xsystem::XSystem};
-use crate::schema::{pool::Pool, pool_state::PoolState};
+use crate::schema::{pool::Pool, pool_state::PoolState, pool_trait::Operation};
 use num_traits::ToPrimitive;
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
@@ -63,6 +63,7 @@ pub fn exec(
   if delta_a == 0 || delta_b == 0 {
     return Err(AppError::ZeroValue.into());
   }
+  Pool::is_valid_ratio(fee_ratio, tax_ratio)?;
 
   // Deposit token A
   util::checked_transfer_splt(
@@ -157,6 +158,8 @@ pub fn exec(
   pool_data.reserve_b = delta_b;
   pool_data.fee_ratio = fee_ratio;
   pool_data.tax_ratio = tax_ratio;
+  pool_data.pauser = *owner.key;
+  pool_data.fee_manager = *owner.key;
   Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
 
   Ok(lpt)
@@ -0,0 +1,48 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::referrers::Referrers;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack},
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let referrers_acc = next_account_info(accounts_iter)?;
+  let authority = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  // The rest are the members
+  let members: Vec<Pubkey> = accounts_iter.map(|member| *member.key).collect();
+
+  util::is_signer(&[payer, referrers_acc])?;
+
+  // Initialize referrers account
+  if !XSystem::check_account(referrers_acc)? {
+    XSystem::rent_account(
+      Referrers::LEN,
+      referrers_acc,
+      payer,
+      program_id,
+      sysvar_rent_acc,
+      system_program,
+    )?;
+  }
+  util::is_program(program_id, &[referrers_acc])?;
+  let mut referrers_data = Referrers::unpack_unchecked(&referrers_acc.data.borrow())?;
+  if referrers_data.is_initialized() {
+    return Err(AppError::AlreadyInitialized.into());
+  }
+  // Update referrers data
+  referrers_data.configure(&members)?;
+  referrers_data.authority = *authority.key;
+  referrers_data.is_initialized = true;
+  Referrers::pack(referrers_data, &mut referrers_acc.data.borrow_mut())?;
+
+  Ok(())
+}
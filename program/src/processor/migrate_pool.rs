@@ -0,0 +1,40 @@
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::pool::{Pool, LEGACY_POOL_LEN};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+///
+/// Upgrade a pool of the legacy layout in place. Anyone can pay for it.
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_signer(&[payer])?;
+
+  let data_len = pool_acc.data_len();
+  if data_len == Pool::LEN {
+    msg!("MigratePool: already up to date");
+    return Ok(());
+  }
+  if data_len != LEGACY_POOL_LEN {
+    return Err(ProgramError::InvalidAccountData);
+  }
+  let pool_data = Pool::unpack_legacy(&pool_acc.data.borrow())?;
+  // Resize and rewrite the pool in the current layout
+  XSystem::realloc_account(Pool::LEN, pool_acc, payer, sysvar_rent_acc, system_program)?;
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok(())
+}
@@ -3,15 +3,42 @@ use solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, msg,
 
 pub mod add_liquidity;
 pub mod add_sided_liquidity;
+pub mod claim_tax;
+pub mod close_pool;
+pub mod distribute_tax;
+pub mod emergency_withdraw;
+pub mod flash_loan;
+pub mod flash_swap;
 pub mod freeze_pool;
+pub mod get_drift;
+pub mod initialize_allowlist;
+pub mod initialize_distribution;
+pub mod initialize_fee_tiers;
+pub mod initialize_multisig;
 pub mod initialize_pool;
+pub mod initialize_referrers;
+pub mod migrate_pool;
 pub mod remove_liquidity;
 pub mod route;
+pub mod set_allowlist;
+pub mod set_fee_tiers;
+pub mod set_referrers;
+pub mod skim;
+pub mod split_route;
+pub mod stake;
 pub mod swap;
+pub mod sync;
 pub mod thaw_pool;
+pub mod transfer_fee_manager;
 pub mod transfer_ownership;
+pub mod transfer_pauser;
 pub mod transfer_taxman;
+pub mod unstake;
+pub mod update_allowlist;
+pub mod update_distribution;
 pub mod update_fee;
+pub mod update_fee_tiers;
+pub mod update_referrers;
 
 pub struct Processor {}
 
@@ -35,7 +62,9 @@ impl Processor {
       }
       AppInstruction::AddLiquidity { delta_a, delta_b } => {
         msg!("Calling AddLiquidity function");
-        add_liquidity::exec(delta_a, delta_b, program_id, accounts)?;
+        let (a, b, lpt, a_remainer, b_remainer) =
+          add_liquidity::exec(delta_a, delta_b, program_id, accounts)?;
+        add_liquidity::report(a, b, lpt, a_remainer, b_remainer);
         Ok(())
       }
       AppInstruction::RemoveLiquidity { lpt } => {
@@ -43,19 +72,23 @@ impl Processor {
         remove_liquidity::exec(lpt, program_id, accounts)?;
         Ok(())
       }
-      AppInstruction::Swap { amount, limit } => {
+      AppInstruction::Swap {
+        amount,
+        limit,
+        referral_ratio,
+      } => {
         msg!("Calling Swap function");
-        swap::exec(amount, limit, program_id, accounts)?;
+        swap::exec(amount, limit, referral_ratio, program_id, accounts)?;
         Ok(())
       }
-      AppInstruction::FreezePool {} => {
+      AppInstruction::FreezePool { mask } => {
         msg!("Calling FreezePool function");
-        freeze_pool::exec(program_id, accounts)?;
+        freeze_pool::exec(mask, program_id, accounts)?;
         Ok(())
       }
-      AppInstruction::ThawPool {} => {
+      AppInstruction::ThawPool { mask } => {
         msg!("Calling ThawPool function");
-        thaw_pool::exec(program_id, accounts)?;
+        thaw_pool::exec(mask, program_id, accounts)?;
         Ok(())
       }
       AppInstruction::TransferTaxman {} => {
@@ -68,17 +101,45 @@ impl Processor {
         transfer_ownership::exec(program_id, accounts)?;
         Ok(())
       }
-      AppInstruction::Route { amount, limit } => {
+      AppInstruction::Route {
+        amount,
+        limit,
+        hop_limits,
+      } => {
         msg!("Calling Route function");
-        route::exec(amount, limit, program_id, accounts)?;
+        route::exec(amount, limit, 0, &hop_limits, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::RouteWithReferral {
+        amount,
+        limit,
+        referral_ratio,
+        hop_limits,
+      } => {
+        msg!("Calling RouteWithReferral function");
+        route::exec(
+          amount,
+          limit,
+          referral_ratio,
+          &hop_limits,
+          program_id,
+          accounts,
+        )?;
         Ok(())
       }
       AppInstruction::UpdateFee {
         fee_ratio,
         tax_ratio,
+        max_referral_ratio,
       } => {
         msg!("Calling UpdateFee function");
-        update_fee::exec(fee_ratio, tax_ratio, program_id, accounts)?;
+        update_fee::exec(
+          fee_ratio,
+          tax_ratio,
+          max_referral_ratio,
+          program_id,
+          accounts,
+        )?;
         Ok(())
       }
       AppInstruction::AddSidedLiquidity { delta_a, delta_b } => {
@@ -86,6 +147,149 @@ impl Processor {
         add_sided_liquidity::exec(delta_a, delta_b, program_id, accounts)?;
         Ok(())
       }
+      AppInstruction::TransferPauser {} => {
+        msg!("Calling TransferPauser function");
+        transfer_pauser::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::TransferFeeManager {} => {
+        msg!("Calling TransferFeeManager function");
+        transfer_fee_manager::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::InitializeMultisig { m } => {
+        msg!("Calling InitializeMultisig function");
+        initialize_multisig::exec(m, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::EmergencyWithdraw { lpt } => {
+        msg!("Calling EmergencyWithdraw function");
+        emergency_withdraw::exec(lpt, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::Sync {} => {
+        msg!("Calling Sync function");
+        sync::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::Skim {} => {
+        msg!("Calling Skim function");
+        skim::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::GetDrift {} => {
+        msg!("Calling GetDrift function");
+        get_drift::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::ClosePool {} => {
+        msg!("Calling ClosePool function");
+        close_pool::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::SplitRoute { limit, paths } => {
+        msg!("Calling SplitRoute function");
+        split_route::exec(limit, &paths, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::FlashLoan {
+        amount_a,
+        amount_b,
+        data,
+      } => {
+        msg!("Calling FlashLoan function");
+        flash_loan::exec(amount_a, amount_b, &data, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::FlashSwap {
+        amount,
+        limit,
+        data,
+      } => {
+        msg!("Calling FlashSwap function");
+        flash_swap::exec(amount, limit, &data, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::ClaimTax {} => {
+        msg!("Calling ClaimTax function");
+        claim_tax::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::InitializeDistribution { shares } => {
+        msg!("Calling InitializeDistribution function");
+        initialize_distribution::exec(&shares, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::UpdateDistribution { shares } => {
+        msg!("Calling UpdateDistribution function");
+        update_distribution::exec(&shares, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::DistributeTax {} => {
+        msg!("Calling DistributeTax function");
+        distribute_tax::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::InitializeFeeTiers { tiers } => {
+        msg!("Calling InitializeFeeTiers function");
+        initialize_fee_tiers::exec(&tiers, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::UpdateFeeTiers { tiers } => {
+        msg!("Calling UpdateFeeTiers function");
+        update_fee_tiers::exec(&tiers, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::SetFeeTiers {} => {
+        msg!("Calling SetFeeTiers function");
+        set_fee_tiers::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::InitializeAllowlist { discount } => {
+        msg!("Calling InitializeAllowlist function");
+        initialize_allowlist::exec(discount, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::UpdateAllowlist { discount, members } => {
+        msg!("Calling UpdateAllowlist function");
+        update_allowlist::exec(discount, members, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::SetAllowlist {} => {
+        msg!("Calling SetAllowlist function");
+        set_allowlist::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::MigratePool {} => {
+        msg!("Calling MigratePool function");
+        migrate_pool::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::InitializeReferrers {} => {
+        msg!("Calling InitializeReferrers function");
+        initialize_referrers::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::UpdateReferrers { members } => {
+        msg!("Calling UpdateReferrers function");
+        update_referrers::exec(members, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::SetReferrers {} => {
+        msg!("Calling SetReferrers function");
+        set_referrers::exec(program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::Stake { amount } => {
+        msg!("Calling Stake function");
+        stake::exec(amount, program_id, accounts)?;
+        Ok(())
+      }
+      AppInstruction::Unstake { amount } => {
+        msg!("Calling Unstake function");
+        unstake::exec(amount, program_id, accounts)?;
+        Ok(())
+      }
     }
   }
 }
@@ -2,8 +2,9 @@ use crate::error::AppError;
 use crate::helper::util;
 use crate::interfaces::xsplt::XSPLT;
 use crate::schema::{
-  pool::Pool,
+  pool_state::PAUSE_WITHDRAW,
   pool_trait::{Exchange, Operation},
+  pool_view::PoolView,
 };
 use num_traits::ToPrimitive;
 use solana_program::{
@@ -11,6 +12,7 @@ use solana_program::{
   program_error::ProgramError,
   program_pack::Pack,
   pubkey::Pubkey,
+  sysvar::{clock::Clock, Sysvar},
 };
 use spl_token::state::Mint;
 use std::result::Result;
@@ -64,16 +66,18 @@ pub fn exec(
   util::is_program(program_id, &[pool_acc])?;
   util::is_signer(&[owner])?;
 
-  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  let pool_acc_data = pool_acc.data.borrow();
+  let pool_view = PoolView::load(&pool_acc_data)?;
+  pool_view.is_unlocked()?;
   let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
-  if pool_data.is_frozen() {
+  if pool_view.is_paused(PAUSE_WITHDRAW) {
     return Err(AppError::FrozenPool.into());
   }
-  if pool_data.mint_lpt != *mint_lpt_acc.key
-    || pool_data.mint_a != *mint_a_acc.key
-    || pool_data.mint_b != *mint_b_acc.key
-    || pool_data.treasury_a != *treasury_a_acc.key
-    || pool_data.treasury_b != *treasury_b_acc.key
+  if pool_view.mint_lpt != mint_lpt_acc.key.to_bytes()
+    || pool_view.mint_a != mint_a_acc.key.to_bytes()
+    || pool_view.mint_b != mint_b_acc.key.to_bytes()
+    || pool_view.treasury_a != treasury_a_acc.key.to_bytes()
+    || pool_view.treasury_b != treasury_b_acc.key.to_bytes()
   {
     return Err(AppError::UnmatchedPool.into());
   }
@@ -83,14 +87,13 @@ pub fn exec(
 
   // Burn lpt
   let mint_lpt_data = Mint::unpack(&mint_lpt_acc.data.borrow())?;
-  let (delta_a, delta_b, _, reserve_a, reserve_b, _) = pool_data
+  let (delta_a, delta_b, _, reserve_a, reserve_b, _) = pool_view
     .withdraw(lpt, mint_lpt_data.supply)
     .ok_or(AppError::Overflow)?;
+  drop(pool_acc_data);
+  // Lock the pool
+  PoolView::load_mut(&mut pool_acc.data.borrow_mut())?.lock();
   XSPLT::burn(lpt, lpt_acc, mint_lpt_acc, owner, splt_program, seed)?;
-  // Update pool
-  pool_data.reserve_a = reserve_a;
-  pool_data.reserve_b = reserve_b;
-  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
   // Withdraw A
   util::checked_transfer_splt(
     delta_a,
@@ -121,6 +124,15 @@ pub fn exec(
     splata_program,
     seed,
   )?;
+  // Update pool
+  let mut pool_acc_data = pool_acc.data.borrow_mut();
+  let pool_view = PoolView::load_mut(&mut pool_acc_data)?;
+  pool_view.set_reserve(0, reserve_a)?;
+  pool_view.set_reserve(1, reserve_b)?;
+  let mut stats = pool_view.stats()?;
+  stats.record_liquidity(Clock::get()?.slot);
+  pool_view.set_stats(&stats);
+  pool_view.unlock();
 
   Ok((delta_a, delta_b))
 }
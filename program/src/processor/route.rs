@@ -6,9 +6,20 @@ use solana_program::{
   pubkey::Pubkey,
 };
 
+///
+/// Maximum number of hops in a route
+///
+pub const MAX_HOPS: usize = 4;
+///
+/// Number of accounts per hop
+///
+pub const HOP_ACCOUNTS: usize = 8;
+
 pub fn exec(
   amount: u64,
   limit: u64,
+  referral_ratio: u64,
+  hop_limits: &[u64],
   program_id: &Pubkey,
   accounts: &[AccountInfo],
 ) -> Result<u64, ProgramError> {
@@ -19,9 +30,27 @@ pub fn exec(
   let sysvar_rent_acc = next_account_info(accounts_iter)?;
   let splata_program = next_account_info(accounts_iter)?;
 
-  // In addition to the shared accounts above, we need 10 more detailed accounts below
+  // In addition to the shared accounts above, we need 8 more detailed accounts per hop below,
+  // then optionally the last pool's referrers and a referrer account (of the last ask mint)
+  let hops = accounts_iter.len() / HOP_ACCOUNTS;
+  let referrer_accounts = match accounts_iter.len() % HOP_ACCOUNTS {
+    0 => None,
+    2 => accounts.get(accounts.len() - 2..),
+    _ => return Err(AppError::InvalidRoute.into()),
+  };
+  if hops == 0 || hops > MAX_HOPS {
+    return Err(AppError::InvalidRoute.into());
+  }
+  if referral_ratio != 0 && referrer_accounts.is_none() {
+    return Err(AppError::InvalidInstruction.into());
+  }
+  if !hop_limits.is_empty() && hop_limits.len() != hops {
+    return Err(AppError::InvalidRoute.into());
+  }
+  let mut visited_pools: Vec<&Pubkey> = Vec::with_capacity(hops);
+  let mut prev_hop: Option<(&Pubkey, &Pubkey)> = None;
   let mut ask_amount = amount;
-  while accounts_iter.len() != 0 {
+  for hop in 0..hops {
     let pool_acc = next_account_info(accounts_iter)?;
     let src_bid_acc = next_account_info(accounts_iter)?;
     let mint_bid_acc = next_account_info(accounts_iter)?;
@@ -29,11 +58,22 @@ pub fn exec(
     let dst_ask_acc = next_account_info(accounts_iter)?;
     let mint_ask_acc = next_account_info(accounts_iter)?;
     let treasury_ask_acc = next_account_info(accounts_iter)?;
-    let taxman_acc = next_account_info(accounts_iter)?;
-    let treasury_taxman_acc = next_account_info(accounts_iter)?;
     let treasurer = next_account_info(accounts_iter)?;
 
-    let swap_accounts: [AccountInfo; 15] = [
+    // The previous ask must be the current bid
+    if let Some((prev_dst_ask, prev_mint_ask)) = prev_hop {
+      if prev_dst_ask != src_bid_acc.key || prev_mint_ask != mint_bid_acc.key {
+        return Err(AppError::InvalidRoute.into());
+      }
+    }
+    // A pool cannot be visited twice
+    if visited_pools.contains(&pool_acc.key) {
+      return Err(AppError::InvalidRoute.into());
+    }
+    visited_pools.push(pool_acc.key);
+    prev_hop = Some((dst_ask_acc.key, mint_ask_acc.key));
+
+    let mut swap_accounts = vec![
       owner.clone(),
       pool_acc.clone(),
       src_bid_acc.clone(),
@@ -42,15 +82,26 @@ pub fn exec(
       dst_ask_acc.clone(),
       mint_ask_acc.clone(),
       treasury_ask_acc.clone(),
-      taxman_acc.clone(),
-      treasury_taxman_acc.clone(),
       treasurer.clone(),
       system_program.clone(),
       splt_program.clone(),
       sysvar_rent_acc.clone(),
       splata_program.clone(),
     ];
-    ask_amount = swap::exec(ask_amount, 0, program_id, &swap_accounts)?;
+    let hop_limit = hop_limits.get(hop).copied().unwrap_or(0);
+    // The referrer is paid on the last hop only
+    let mut hop_referral_ratio = 0;
+    if let (Some(referrer_accounts), true) = (referrer_accounts, hop == hops - 1) {
+      swap_accounts.extend_from_slice(referrer_accounts);
+      hop_referral_ratio = referral_ratio;
+    }
+    ask_amount = swap::exec(
+      ask_amount,
+      hop_limit,
+      hop_referral_ratio,
+      program_id,
+      &swap_accounts,
+    )?;
   }
 
   if ask_amount < limit {
@@ -0,0 +1,38 @@
+use crate::helper::util;
+use crate::schema::{allowlist::Allowlist, pool::Pool, pool_trait::Operation};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+  system_program,
+};
+use std::result::Result;
+
+///
+/// Attach an allowlist account to the pool (or detach it by passing the system program)
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let owner = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+  let allowlist_acc = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_authority(program_id, owner, signers)?;
+  if *allowlist_acc.key != system_program::id() {
+    util::is_program(program_id, &[allowlist_acc])?;
+    Allowlist::unpack(&allowlist_acc.data.borrow())?;
+  }
+
+  // Update pool data
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  pool_data.is_owner(*owner.key)?;
+  pool_data.allowlist = *allowlist_acc.key;
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok(())
+}
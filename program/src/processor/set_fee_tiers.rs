@@ -0,0 +1,38 @@
+use crate::helper::util;
+use crate::schema::{fee_tiers::FeeTiers, pool::Pool, pool_trait::Operation};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+  system_program,
+};
+use std::result::Result;
+
+///
+/// Attach a fee tiers account to the pool (or detach it by passing the system program)
+///
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let fee_manager = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+  let fee_tiers_acc = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_authority(program_id, fee_manager, signers)?;
+  if *fee_tiers_acc.key != system_program::id() {
+    util::is_program(program_id, &[fee_tiers_acc])?;
+    FeeTiers::unpack(&fee_tiers_acc.data.borrow())?;
+  }
+
+  // Update pool data
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  pool_data.is_fee_manager(*fee_manager.key)?;
+  pool_data.fee_tiers = *fee_tiers_acc.key;
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok(())
+}
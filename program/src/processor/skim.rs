@@ -0,0 +1,117 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::{
+  pool::{drift, Pool},
+  pool_trait::Operation,
+};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use spl_token::state::Account;
+use std::result::Result;
+
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(u64, u64), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let payer = next_account_info(accounts_iter)?;
+  let pool_acc = next_account_info(accounts_iter)?;
+
+  let mint_a_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+
+  let mint_b_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  let taxman_acc = next_account_info(accounts_iter)?;
+  let treasury_taxman_a_acc = next_account_info(accounts_iter)?;
+  let treasury_taxman_b_acc = next_account_info(accounts_iter)?;
+
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+
+  util::is_program(program_id, &[pool_acc])?;
+  util::is_signer(&[payer])?;
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
+  if pool_data.mint_a != *mint_a_acc.key
+    || pool_data.mint_b != *mint_b_acc.key
+    || pool_data.treasury_a != *treasury_a_acc.key
+    || pool_data.treasury_b != *treasury_b_acc.key
+    || pool_data.taxman != *taxman_acc.key
+  {
+    return Err(AppError::UnmatchedPool.into());
+  }
+
+  // Only the positive drift is skimmed
+  let treasury_a_data = Account::unpack(&treasury_a_acc.data.borrow())?;
+  let treasury_b_data = Account::unpack(&treasury_b_acc.data.borrow())?;
+  // The accrued tax is held in the treasuries but excluded from the reserves
+  let recorded_a = pool_data
+    .reserve_a
+    .checked_add(pool_data.tax_a)
+    .ok_or(AppError::Overflow)?;
+  let recorded_b = pool_data
+    .reserve_b
+    .checked_add(pool_data.tax_b)
+    .ok_or(AppError::Overflow)?;
+  let (drift_a, drift_b) = drift(
+    treasury_a_data.amount,
+    treasury_b_data.amount,
+    recorded_a,
+    recorded_b,
+  );
+  msg!("Drift: {} {}", drift_a, drift_b);
+  let excess_a = treasury_a_data.amount.saturating_sub(recorded_a);
+  let excess_b = treasury_b_data.amount.saturating_sub(recorded_b);
+  // Lock the pool
+  pool_data.lock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  // Skim A
+  if excess_a != 0 {
+    util::checked_transfer_splt(
+      excess_a,
+      payer,
+      treasury_a_acc,
+      treasurer,
+      treasury_taxman_a_acc,
+      taxman_acc,
+      mint_a_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+  // Skim B
+  if excess_b != 0 {
+    util::checked_transfer_splt(
+      excess_b,
+      payer,
+      treasury_b_acc,
+      treasurer,
+      treasury_taxman_b_acc,
+      taxman_acc,
+      mint_b_acc,
+      system_program,
+      splt_program,
+      sysvar_rent_acc,
+      splata_program,
+      seed,
+    )?;
+  }
+
+  // Unlock the pool
+  pool_data.unlock();
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok((excess_a, excess_b))
+}
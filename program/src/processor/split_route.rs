@@ -0,0 +1,75 @@
+use crate::error::AppError;
+use crate::processor::route::{self, HOP_ACCOUNTS};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  pubkey::Pubkey,
+};
+
+///
+/// Maximum number of parallel paths
+///
+pub const MAX_PATHS: usize = 4;
+
+pub fn exec(
+  limit: u64,
+  paths: &[(u64, u8)],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<u64, ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let owner = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+
+  // In addition to the shared accounts above, each path needs its hops of route::HOP_ACCOUNTS below
+  if paths.is_empty() || paths.len() > MAX_PATHS {
+    return Err(AppError::InvalidRoute.into());
+  }
+  let mut dst: Option<(&Pubkey, &Pubkey)> = None;
+  let mut ask_amount: u64 = 0;
+  for &(amount, hops) in paths.iter() {
+    let mut path_accounts: Vec<AccountInfo> = vec![
+      owner.clone(),
+      system_program.clone(),
+      splt_program.clone(),
+      sysvar_rent_acc.clone(),
+      splata_program.clone(),
+    ];
+    for _ in 0..(hops as usize * HOP_ACCOUNTS) {
+      path_accounts.push(next_account_info(accounts_iter)?.clone());
+    }
+    // All paths must end at the same destination
+    let last_hop = path_accounts.len().saturating_sub(HOP_ACCOUNTS);
+    let dst_ask_acc = path_accounts
+      .get(last_hop + 4)
+      .ok_or(AppError::InvalidRoute)?;
+    let mint_ask_acc = path_accounts
+      .get(last_hop + 5)
+      .ok_or(AppError::InvalidRoute)?;
+    match dst {
+      Some((dst_ask, mint_ask)) => {
+        if dst_ask != dst_ask_acc.key || mint_ask != mint_ask_acc.key {
+          return Err(AppError::InvalidRoute.into());
+        }
+      }
+      None => dst = Some((dst_ask_acc.key, mint_ask_acc.key)),
+    }
+    // Execute the path through the linear route
+    let path_ask_amount = route::exec(amount, 0, 0, &[], program_id, &path_accounts)?;
+    ask_amount = ask_amount
+      .checked_add(path_ask_amount)
+      .ok_or(AppError::Overflow)?;
+  }
+  if accounts_iter.len() != 0 {
+    return Err(AppError::InvalidRoute.into());
+  }
+
+  if ask_amount < limit {
+    return Err(AppError::ExceedLimit.into());
+  }
+
+  Ok(ask_amount)
+}
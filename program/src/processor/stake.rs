@@ -0,0 +1,98 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::interfaces::xsystem::XSystem;
+use crate::schema::stake::Stake;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack},
+  pubkey::Pubkey,
+  sysvar::{clock::Clock, Sysvar},
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::result::Result;
+
+///
+/// Lock governance tokens in the vault of a stake account (created on the first deposit).
+/// Every deposit restarts the holding period.
+///
+pub fn exec(
+  amount: u64,
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let owner = next_account_info(accounts_iter)?;
+  let stake_acc = next_account_info(accounts_iter)?;
+
+  let src_acc = next_account_info(accounts_iter)?;
+  let mint_acc = next_account_info(accounts_iter)?;
+  let vault_acc = next_account_info(accounts_iter)?;
+
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+
+  util::is_signer(&[owner])?;
+  util::safe_seed(stake_acc, treasurer, program_id)?;
+  if amount == 0 {
+    return Err(AppError::ZeroValue.into());
+  }
+
+  // Initialize stake account
+  if !XSystem::check_account(stake_acc)? {
+    util::is_signer(&[stake_acc])?;
+    XSystem::rent_account(
+      Stake::LEN,
+      stake_acc,
+      owner,
+      program_id,
+      sysvar_rent_acc,
+      system_program,
+    )?;
+  }
+  util::is_program(program_id, &[stake_acc])?;
+  let mut stake_data = Stake::unpack_unchecked(&stake_acc.data.borrow())?;
+  if !stake_data.is_initialized() {
+    if *vault_acc.key != get_associated_token_address(treasurer.key, mint_acc.key) {
+      return Err(AppError::InvalidStake.into());
+    }
+    stake_data.owner = *owner.key;
+    stake_data.mint = *mint_acc.key;
+    stake_data.vault = *vault_acc.key;
+    stake_data.is_initialized = true;
+  }
+  if stake_data.owner != *owner.key {
+    return Err(AppError::InvalidOwner.into());
+  }
+  if stake_data.mint != *mint_acc.key || stake_data.vault != *vault_acc.key {
+    return Err(AppError::InvalidStake.into());
+  }
+
+  // Deposit into the vault
+  util::checked_transfer_splt(
+    amount,
+    owner,
+    src_acc,
+    owner,
+    vault_acc,
+    treasurer,
+    mint_acc,
+    system_program,
+    splt_program,
+    sysvar_rent_acc,
+    splata_program,
+    &[],
+  )?;
+  // Update stake data
+  stake_data.amount = stake_data
+    .amount
+    .checked_add(amount)
+    .ok_or(AppError::Overflow)?;
+  stake_data.staked_at = Clock::get()?.slot;
+  Stake::pack(stake_data, &mut stake_acc.data.borrow_mut())?;
+
+  Ok(())
+}
@@ -2,20 +2,29 @@ use crate::error::AppError;
 use crate::helper::util;
 use crate::interfaces::xsplt::XSPLT;
 use crate::schema::{
-  pool::Pool,
+  allowlist::Allowlist,
+  fee_tiers::FeeTiers,
+  pool_state::PAUSE_SWAP,
   pool_trait::{Exchange, Operation},
+  pool_view::PoolView,
+  referrers::Referrers,
+  stake::Stake,
 };
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
   program_error::ProgramError,
   program_pack::Pack,
   pubkey::Pubkey,
+  system_program,
+  sysvar::{clock::Clock, instructions, Sysvar},
 };
+use spl_token::state::Account;
 use std::result::Result;
 
 pub fn exec(
   amount: u64,
   limit: u64,
+  referral_ratio: u64,
   program_id: &Pubkey,
   accounts: &[AccountInfo],
 ) -> Result<u64, ProgramError> {
@@ -31,21 +40,49 @@ pub fn exec(
   let mint_ask_acc = next_account_info(accounts_iter)?;
   let treasury_ask_acc = next_account_info(accounts_iter)?;
 
-  let taxman_acc = next_account_info(accounts_iter)?;
-  let treasury_taxman_acc = next_account_info(accounts_iter)?;
-
   let treasurer = next_account_info(accounts_iter)?;
   let system_program = next_account_info(accounts_iter)?;
   let splt_program = next_account_info(accounts_iter)?;
   let sysvar_rent_acc = next_account_info(accounts_iter)?;
   let splata_program = next_account_info(accounts_iter)?;
+  // Optionally the pool's referrers and a referrer account (of the ask mint), then optional
+  // fee tiers and the owner's stake, then optionally the instructions sysvar and the pool's
+  // allowlist
+  let extra_accounts = accounts_iter.as_slice();
 
   util::is_program(program_id, &[pool_acc])?;
   util::is_signer(&[owner])?;
 
-  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  let pool_acc_data = pool_acc.data.borrow();
+  let pool_view = PoolView::load(&pool_acc_data)?;
+  pool_view.is_unlocked()?;
   let seed: &[&[&[u8]]] = &[&[&util::safe_seed(pool_acc, treasurer, program_id)?[..]]];
-  if pool_data.is_frozen() {
+  let (allowlist_acc, extra_accounts) = match extra_accounts.split_last() {
+    Some((last, rest))
+      if pool_view.allowlist != system_program::id().to_bytes()
+        && last.key.to_bytes() == pool_view.allowlist =>
+    {
+      (Some(last), rest)
+    }
+    _ => (None, extra_accounts),
+  };
+  let (instructions_acc, extra_accounts) = match (allowlist_acc, extra_accounts.split_last()) {
+    (Some(_), Some((last, rest))) if instructions::check_id(last.key) => (Some(last), rest),
+    _ => (None, extra_accounts),
+  };
+  let (referrer_accounts, tier_accounts) = match extra_accounts {
+    [referrers_acc, referrer_acc, rest @ ..]
+      if pool_view.referrers != system_program::id().to_bytes()
+        && referrers_acc.key.to_bytes() == pool_view.referrers =>
+    {
+      (Some((referrers_acc, referrer_acc)), rest)
+    }
+    _ => (None, extra_accounts),
+  };
+  if !matches!(tier_accounts.len(), 0 | 2) {
+    return Err(AppError::InvalidInstruction.into());
+  }
+  if pool_view.is_paused(PAUSE_SWAP) {
     return Err(AppError::FrozenPool.into());
   }
   if *mint_bid_acc.key == *mint_ask_acc.key {
@@ -54,20 +91,62 @@ pub fn exec(
   if amount == 0 {
     return Err(AppError::ZeroValue.into());
   }
+  let (_, _, max_referral_ratio) = pool_view.ratios();
+  if referral_ratio > max_referral_ratio {
+    return Err(AppError::InvalidRatio.into());
+  }
+  // Only a registered referrer takes a referral
+  let referrer_acc = match referrer_accounts {
+    Some((referrers_acc, referrer_acc)) => {
+      registered_referrer(referrers_acc, referrer_acc, program_id)?;
+      Some(referrer_acc)
+    }
+    None if referral_ratio != 0 => return Err(AppError::InvalidInstruction.into()),
+    None => None,
+  };
 
   let bid_amount = amount;
-  let (temp_ask_amount, new_bid_reserve, temp_new_ask_reserve) = pool_data
+  let (temp_ask_amount, new_bid_reserve, temp_new_ask_reserve) = pool_view
     .curve(bid_amount, mint_bid_acc.key, mint_ask_acc.key)
     .ok_or(AppError::Overflow)?;
-  let (ask_amount, fee, tax) = pool_data.fee(temp_ask_amount).ok_or(AppError::Overflow)?;
+  // Allowlisted members skip the tax, and take precedence over the fee tiers
+  let (discount, tax_exempt) = match (allowlist_acc, tier_accounts) {
+    (Some(allowlist_acc), _) => (
+      allowlist_discount(owner, allowlist_acc, instructions_acc, program_id)?,
+      true,
+    ),
+    (None, [fee_tiers_acc, stake_acc]) => (
+      fee_discount(pool_view, owner, fee_tiers_acc, stake_acc, program_id)?,
+      false,
+    ),
+    _ => (0, false),
+  };
+  let (ask_amount, fee, tax) = pool_view
+    .discounted_fee(temp_ask_amount, discount, tax_exempt)
+    .ok_or(AppError::Overflow)?;
+  // The referrer takes a slice of the fee
+  let referral = pool_view
+    .referral(fee, referral_ratio)
+    .ok_or(AppError::Overflow)?;
   let new_ask_reserve = temp_new_ask_reserve
     .checked_add(fee)
+    .and_then(|reserve| reserve.checked_sub(referral))
     .ok_or(AppError::Overflow)?;
 
   if ask_amount < limit {
     return Err(AppError::ExceedLimit.into());
   }
 
+  let (bid_code, _) = pool_view
+    .get_reserve(mint_bid_acc.key)
+    .ok_or(AppError::UnmatchedPool)?;
+  let (ask_code, _) = pool_view
+    .get_reserve(mint_ask_acc.key)
+    .ok_or(AppError::UnmatchedPool)?;
+  drop(pool_acc_data);
+
+  // Lock the pool (in place, the hops of a route come through here)
+  PoolView::load_mut(&mut pool_acc.data.borrow_mut())?.lock();
   // Execute bid
   XSPLT::transfer(
     bid_amount,
@@ -77,31 +156,6 @@ pub fn exec(
     splt_program,
     &[],
   )?;
-  let (bid_code, _) = pool_data
-    .get_reserve(mint_bid_acc.key)
-    .ok_or(AppError::UnmatchedPool)?;
-  match bid_code {
-    0 => pool_data.reserve_a = new_bid_reserve,
-    1 => pool_data.reserve_b = new_bid_reserve,
-    _ => return Err(AppError::UnmatchedPool.into()),
-  }
-  // Pay tax (Initialize ask account if not exsting)
-  if tax != 0 {
-    util::checked_transfer_splt(
-      tax,
-      owner,
-      treasury_ask_acc,
-      treasurer,
-      treasury_taxman_acc,
-      taxman_acc,
-      mint_ask_acc,
-      system_program,
-      splt_program,
-      sysvar_rent_acc,
-      splata_program,
-      seed,
-    )?;
-  }
   // Execute ask (Initialize ask account if not exsting)
   util::checked_transfer_splt(
     ask_amount,
@@ -117,15 +171,105 @@ pub fn exec(
     splata_program,
     seed,
   )?;
-  let (ask_code, _) = pool_data
-    .get_reserve(mint_ask_acc.key)
-    .ok_or(AppError::UnmatchedPool)?;
-  match ask_code {
-    0 => pool_data.reserve_a = new_ask_reserve,
-    1 => pool_data.reserve_b = new_ask_reserve,
-    _ => return Err(AppError::UnmatchedPool.into()),
+  // Pay the referrer
+  if referral != 0 {
+    let referrer_acc = referrer_acc.ok_or(AppError::InvalidInstruction)?;
+    XSPLT::transfer(
+      referral,
+      treasury_ask_acc,
+      referrer_acc,
+      treasurer,
+      splt_program,
+      seed,
+    )?;
   }
   // Update pool
-  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+  let mut pool_acc_data = pool_acc.data.borrow_mut();
+  let pool_view = PoolView::load_mut(&mut pool_acc_data)?;
+  pool_view.set_reserve(bid_code, new_bid_reserve)?;
+  pool_view.set_reserve(ask_code, new_ask_reserve)?;
+  // Accrue tax for the taxman to claim
+  pool_view
+    .accrue_tax(mint_ask_acc.key, tax)
+    .ok_or(AppError::Overflow)?;
+  // Update stats (the LPs earn the fee less the referral)
+  let mut stats = pool_view.stats()?;
+  stats.record_swap(
+    (bid_code, bid_amount),
+    (ask_code, ask_amount),
+    fee - referral,
+    tax,
+    Clock::get()?.slot,
+  );
+  pool_view.set_stats(&stats);
+  pool_view.unlock();
   Ok(ask_amount)
 }
+
+///
+/// Fee discount of the owner's governance token stake
+///
+pub fn fee_discount(
+  pool_view: &PoolView,
+  owner: &AccountInfo,
+  fee_tiers_acc: &AccountInfo,
+  stake_acc: &AccountInfo,
+  program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+  util::is_program(program_id, &[fee_tiers_acc])?;
+  util::is_program(program_id, &[stake_acc])?;
+  if pool_view.fee_tiers != fee_tiers_acc.key.to_bytes() {
+    return Err(AppError::InvalidFeeTiers.into());
+  }
+  let fee_tiers_data = FeeTiers::unpack(&fee_tiers_acc.data.borrow())?;
+  let stake_data = Stake::unpack(&stake_acc.data.borrow())?;
+  if stake_data.owner != *owner.key || stake_data.mint != fee_tiers_data.mint {
+    return Err(AppError::InvalidOwner.into());
+  }
+  Ok(fee_tiers_data.discount(stake_data.holding(Clock::get()?.slot)))
+}
+
+///
+/// Check that the referrer account belongs to a wallet registered in the pool's referrers
+///
+pub fn registered_referrer(
+  referrers_acc: &AccountInfo,
+  referrer_acc: &AccountInfo,
+  program_id: &Pubkey,
+) -> Result<(), ProgramError> {
+  util::is_program(program_id, &[referrers_acc])?;
+  if *referrer_acc.owner != spl_token::id() {
+    return Err(AppError::IncorrectProgramId.into());
+  }
+  let referrers_data = Referrers::unpack(&referrers_acc.data.borrow())?;
+  let referrer_data = Account::unpack(&referrer_acc.data.borrow())?;
+  if !referrers_data.contains(&referrer_data.owner) {
+    return Err(AppError::InvalidOwner.into());
+  }
+  Ok(())
+}
+
+///
+/// Fee discount of an allowlisted owner, or of an allowlisted program calling the swap
+/// (named by the instructions sysvar)
+///
+pub fn allowlist_discount(
+  owner: &AccountInfo,
+  allowlist_acc: &AccountInfo,
+  instructions_acc: Option<&AccountInfo>,
+  program_id: &Pubkey,
+) -> Result<u64, ProgramError> {
+  util::is_program(program_id, &[allowlist_acc])?;
+  let allowlist_data = Allowlist::unpack(&allowlist_acc.data.borrow())?;
+  if allowlist_data.contains(owner.key) {
+    return Ok(allowlist_data.discount);
+  }
+  if let Some(instructions_acc) = instructions_acc {
+    if let Some(caller) = util::calling_program(instructions_acc)? {
+      if allowlist_data.contains(&caller) {
+        return Ok(allowlist_data.discount);
+      }
+    }
+  }
+  Err(AppError::InvalidOwner.into())
+}
@@ -0,0 +1,61 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::{
+  pool::{drift, Pool},
+  pool_trait::Operation,
+};
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  msg,
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use spl_token::state::Account;
+use std::result::Result;
+
+pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let pool_acc = next_account_info(accounts_iter)?;
+  let treasury_a_acc = next_account_info(accounts_iter)?;
+  let treasury_b_acc = next_account_info(accounts_iter)?;
+
+  util::is_program(program_id, &[pool_acc])?;
+
+  let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
+  if pool_data.treasury_a != *treasury_a_acc.key || pool_data.treasury_b != *treasury_b_acc.key {
+    return Err(AppError::UnmatchedPool.into());
+  }
+
+  // Set the reserves to the actual balances
+  let treasury_a_data = Account::unpack(&treasury_a_acc.data.borrow())?;
+  let treasury_b_data = Account::unpack(&treasury_b_acc.data.borrow())?;
+  // The accrued tax is held in the treasuries but excluded from the reserves
+  let recorded_a = pool_data
+    .reserve_a
+    .checked_add(pool_data.tax_a)
+    .ok_or(AppError::Overflow)?;
+  let recorded_b = pool_data
+    .reserve_b
+    .checked_add(pool_data.tax_b)
+    .ok_or(AppError::Overflow)?;
+  let (drift_a, drift_b) = drift(
+    treasury_a_data.amount,
+    treasury_b_data.amount,
+    recorded_a,
+    recorded_b,
+  );
+  msg!("Drift: {} {}", drift_a, drift_b);
+  pool_data.reserve_a = treasury_a_data
+    .amount
+    .checked_sub(pool_data.tax_a)
+    .ok_or(AppError::Overflow)?;
+  pool_data.reserve_b = treasury_b_data
+    .amount
+    .checked_sub(pool_data.tax_b)
+    .ok_or(AppError::Overflow)?;
+  Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
+
+  Ok(())
+}
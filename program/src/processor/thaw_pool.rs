@@ -1,5 +1,5 @@
 use crate::helper::util;
-use crate::schema::{pool::Pool, pool_state::PoolState, pool_trait::Operation};
+use crate::schema::{pool::Pool, pool_trait::Operation};
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
   program_error::ProgramError,
@@ -8,17 +8,20 @@ use solana_program::{
 };
 use std::result::Result;
 
-pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
+pub fn exec(mask: u8, program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), ProgramError> {
   let accounts_iter = &mut accounts.iter();
   let owner = next_account_info(accounts_iter)?;
   let pool_acc = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
 
   util::is_program(program_id, &[pool_acc])?;
-  util::is_signer(&[owner])?;
+  util::is_authority(program_id, owner, signers)?;
 
   let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
   pool_data.is_owner(*owner.key)?;
-  pool_data.state = PoolState::Initialized;
+  pool_data.unpause(mask);
   Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
 
   Ok(())
@@ -13,12 +13,15 @@ pub fn exec(program_id: &Pubkey, accounts: &[AccountInfo]) -> Result<(), Program
   let owner = next_account_info(accounts_iter)?;
   let pool_acc = next_account_info(accounts_iter)?;
   let new_taxman_acc = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
 
   util::is_program(program_id, &[pool_acc])?;
-  util::is_signer(&[owner])?;
+  util::is_authority(program_id, owner, signers)?;
 
   // Update pool data
   let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
+  pool_data.is_unlocked()?;
   pool_data.is_owner(*owner.key)?;
   pool_data.taxman = *new_taxman_acc.key;
   Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
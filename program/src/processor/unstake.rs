@@ -0,0 +1,72 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::stake::Stake;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+///
+/// Withdraw governance tokens from the vault of a stake account
+///
+pub fn exec(
+  amount: u64,
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let owner = next_account_info(accounts_iter)?;
+  let stake_acc = next_account_info(accounts_iter)?;
+
+  let dst_acc = next_account_info(accounts_iter)?;
+  let mint_acc = next_account_info(accounts_iter)?;
+  let vault_acc = next_account_info(accounts_iter)?;
+
+  let treasurer = next_account_info(accounts_iter)?;
+  let system_program = next_account_info(accounts_iter)?;
+  let splt_program = next_account_info(accounts_iter)?;
+  let sysvar_rent_acc = next_account_info(accounts_iter)?;
+  let splata_program = next_account_info(accounts_iter)?;
+
+  util::is_program(program_id, &[stake_acc])?;
+  util::is_signer(&[owner])?;
+
+  let mut stake_data = Stake::unpack(&stake_acc.data.borrow())?;
+  let seed: &[&[&[u8]]] = &[&[&util::safe_seed(stake_acc, treasurer, program_id)?[..]]];
+  if stake_data.owner != *owner.key {
+    return Err(AppError::InvalidOwner.into());
+  }
+  if stake_data.mint != *mint_acc.key || stake_data.vault != *vault_acc.key {
+    return Err(AppError::InvalidStake.into());
+  }
+  if amount == 0 {
+    return Err(AppError::ZeroValue.into());
+  }
+
+  // Update stake data
+  stake_data.amount = stake_data
+    .amount
+    .checked_sub(amount)
+    .ok_or(AppError::Overflow)?;
+  Stake::pack(stake_data, &mut stake_acc.data.borrow_mut())?;
+  // Withdraw from the vault
+  util::checked_transfer_splt(
+    amount,
+    owner,
+    vault_acc,
+    treasurer,
+    dst_acc,
+    owner,
+    mint_acc,
+    system_program,
+    splt_program,
+    sysvar_rent_acc,
+    splata_program,
+    seed,
+  )?;
+
+  Ok(())
+}
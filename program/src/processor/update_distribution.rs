@@ -0,0 +1,40 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::distribution::Distribution;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(
+  shares: &[u16],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let authority = next_account_info(accounts_iter)?;
+  let distribution_acc = next_account_info(accounts_iter)?;
+  // Then the recipients, in the order of the shares
+  let mut recipients = Vec::with_capacity(shares.len());
+  for _ in 0..shares.len() {
+    recipients.push(*next_account_info(accounts_iter)?.key);
+  }
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[distribution_acc])?;
+  util::is_authority(program_id, authority, signers)?;
+
+  // Update distribution data
+  let mut distribution_data = Distribution::unpack(&distribution_acc.data.borrow())?;
+  if distribution_data.authority != *authority.key {
+    return Err(AppError::InvalidOwner.into());
+  }
+  distribution_data.configure(&recipients, shares)?;
+  Distribution::pack(distribution_data, &mut distribution_acc.data.borrow_mut())?;
+
+  Ok(())
+}
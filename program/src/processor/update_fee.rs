@@ -1,5 +1,9 @@
+use crate::error::AppError;
 use crate::helper::util;
-use crate::schema::{pool::Pool, pool_trait::Operation};
+use crate::schema::{
+  pool::{Pool, MAX_REFERRAL_RATIO},
+  pool_trait::Operation,
+};
 use solana_program::{
   account_info::{next_account_info, AccountInfo},
   program_error::ProgramError,
@@ -11,21 +15,32 @@ use std::result::Result;
 pub fn exec(
   fee_ratio: u64,
   tax_ratio: u64,
+  max_referral_ratio: Option<u64>,
   program_id: &Pubkey,
   accounts: &[AccountInfo],
 ) -> Result<(), ProgramError> {
   let accounts_iter = &mut accounts.iter();
-  let owner = next_account_info(accounts_iter)?;
+  let fee_manager = next_account_info(accounts_iter)?;
   let pool_acc = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
 
   util::is_program(program_id, &[pool_acc])?;
-  util::is_signer(&[owner])?;
+  util::is_authority(program_id, fee_manager, signers)?;
+  Pool::is_valid_ratio(fee_ratio, tax_ratio)?;
 
   // Update pool data
   let mut pool_data = Pool::unpack(&pool_acc.data.borrow())?;
-  pool_data.is_owner(*owner.key)?;
+  pool_data.is_unlocked()?;
+  pool_data.is_fee_manager(*fee_manager.key)?;
   pool_data.fee_ratio = fee_ratio;
   pool_data.tax_ratio = tax_ratio;
+  if let Some(max_referral_ratio) = max_referral_ratio {
+    if max_referral_ratio > MAX_REFERRAL_RATIO {
+      return Err(AppError::InvalidRatio.into());
+    }
+    pool_data.max_referral_ratio = max_referral_ratio;
+  }
   Pool::pack(pool_data, &mut pool_acc.data.borrow_mut())?;
 
   Ok(())
@@ -0,0 +1,35 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::fee_tiers::FeeTiers;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(
+  tiers: &[(u64, u64)],
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let authority = next_account_info(accounts_iter)?;
+  let fee_tiers_acc = next_account_info(accounts_iter)?;
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[fee_tiers_acc])?;
+  util::is_authority(program_id, authority, signers)?;
+
+  // Update fee tiers data
+  let mut fee_tiers_data = FeeTiers::unpack(&fee_tiers_acc.data.borrow())?;
+  if fee_tiers_data.authority != *authority.key {
+    return Err(AppError::InvalidOwner.into());
+  }
+  fee_tiers_data.configure(tiers)?;
+  FeeTiers::pack(fee_tiers_data, &mut fee_tiers_acc.data.borrow_mut())?;
+
+  Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::error::AppError;
+use crate::helper::util;
+use crate::schema::referrers::Referrers;
+use solana_program::{
+  account_info::{next_account_info, AccountInfo},
+  program_error::ProgramError,
+  program_pack::Pack,
+  pubkey::Pubkey,
+};
+use std::result::Result;
+
+pub fn exec(
+  members: u8,
+  program_id: &Pubkey,
+  accounts: &[AccountInfo],
+) -> Result<(), ProgramError> {
+  let accounts_iter = &mut accounts.iter();
+  let authority = next_account_info(accounts_iter)?;
+  let referrers_acc = next_account_info(accounts_iter)?;
+  // Then the members
+  let mut member_keys = Vec::with_capacity(members as usize);
+  for _ in 0..members {
+    member_keys.push(*next_account_info(accounts_iter)?.key);
+  }
+  // The rest are the multisig signers (if any)
+  let signers = accounts_iter.as_slice();
+
+  util::is_program(program_id, &[referrers_acc])?;
+  util::is_authority(program_id, authority, signers)?;
+
+  // Update referrers data
+  let mut referrers_data = Referrers::unpack(&referrers_acc.data.borrow())?;
+  if referrers_data.authority != *authority.key {
+    return Err(AppError::InvalidOwner.into());
+  }
+  referrers_data.configure(&member_keys)?;
+  Referrers::pack(referrers_data, &mut referrers_acc.data.borrow_mut())?;
+
+  Ok(())
+}
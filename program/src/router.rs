@@ -0,0 +1,372 @@
+use crate::processor::{route::MAX_HOPS, split_route::MAX_PATHS};
+use crate::schema::{
+  pool::Pool,
+  pool_state::PAUSE_SWAP,
+  pool_trait::{Exchange, Operation},
+};
+use solana_program::{
+  instruction::{AccountMeta, Instruction},
+  pubkey::Pubkey,
+  system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::collections::HashMap;
+
+///
+/// A simulated swap on a pool
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hop {
+  pub pool: Pubkey,
+  pub bid_mint: Pubkey,
+  pub ask_mint: Pubkey,
+  pub bid_amount: u64,
+  pub ask_amount: u64,
+}
+
+///
+/// A linear sequence of hops
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+  pub hops: Vec<Hop>,
+}
+
+impl Path {
+  pub fn bid_amount(&self) -> u64 {
+    self.hops.first().map(|hop| hop.bid_amount).unwrap_or(0)
+  }
+  pub fn ask_amount(&self) -> u64 {
+    self.hops.last().map(|hop| hop.ask_amount).unwrap_or(0)
+  }
+}
+
+///
+/// Candidate leg of a path (pool, bid mint, ask mint)
+///
+type Leg = (Pubkey, Pubkey, Pubkey);
+
+///
+/// Off-chain router over decoded pool states
+///
+#[derive(Clone, Debug, Default)]
+pub struct Router {
+  pub pools: HashMap<Pubkey, Pool>,
+}
+
+impl Router {
+  pub fn new(pools: &[(Pubkey, Pool)]) -> Self {
+    Router {
+      pools: pools.iter().cloned().collect(),
+    }
+  }
+
+  ///
+  /// Simulate a swap on a pool state the same way swap::exec does
+  ///
+  fn swap(pool: &mut Pool, bid_amount: u64, bid_mint: &Pubkey, ask_mint: &Pubkey) -> Option<u64> {
+    if pool.is_paused(PAUSE_SWAP) || bid_amount == 0 || bid_mint == ask_mint {
+      return None;
+    }
+    let (temp_ask_amount, new_bid_reserve, temp_new_ask_reserve) =
+      pool.curve(bid_amount, bid_mint, ask_mint)?;
+    let (ask_amount, fee, _) = pool.fee(temp_ask_amount)?;
+    let new_ask_reserve = temp_new_ask_reserve.checked_add(fee)?;
+    let (bid_code, _) = pool.get_reserve(bid_mint)?;
+    let (ask_code, _) = pool.get_reserve(ask_mint)?;
+    match bid_code {
+      0 => pool.reserve_a = new_bid_reserve,
+      _ => pool.reserve_b = new_bid_reserve,
+    }
+    match ask_code {
+      0 => pool.reserve_a = new_ask_reserve,
+      _ => pool.reserve_b = new_ask_reserve,
+    }
+    Some(ask_amount)
+  }
+
+  ///
+  /// Simulate a path of legs against (and update) the given pool states
+  ///
+  fn simulate(pools: &mut HashMap<Pubkey, Pool>, legs: &[Leg], amount: u64) -> Option<Path> {
+    let mut hops = Vec::with_capacity(legs.len());
+    let mut bid_amount = amount;
+    for (pool_id, bid_mint, ask_mint) in legs.iter() {
+      let pool = pools.get_mut(pool_id)?;
+      let ask_amount = Self::swap(pool, bid_amount, bid_mint, ask_mint)?;
+      hops.push(Hop {
+        pool: *pool_id,
+        bid_mint: *bid_mint,
+        ask_mint: *ask_mint,
+        bid_amount,
+        ask_amount,
+      });
+      bid_amount = ask_amount;
+    }
+    Some(Path { hops })
+  }
+
+  ///
+  /// Enumerate all paths up to max_hops that never revisit a pool nor a mint
+  ///
+  pub fn legs(&self, bid_mint: &Pubkey, ask_mint: &Pubkey, max_hops: usize) -> Vec<Vec<Leg>> {
+    let mut candidates = vec![];
+    let mut stack: Vec<Leg> = vec![];
+    let mut pool_ids: Vec<&Pubkey> = self.pools.keys().collect();
+    pool_ids.sort();
+    self.search(
+      bid_mint,
+      ask_mint,
+      max_hops.min(MAX_HOPS),
+      &pool_ids,
+      &mut stack,
+      &mut candidates,
+    );
+    candidates
+  }
+
+  fn search(
+    &self,
+    mint: &Pubkey,
+    ask_mint: &Pubkey,
+    max_hops: usize,
+    pool_ids: &[&Pubkey],
+    stack: &mut Vec<Leg>,
+    candidates: &mut Vec<Vec<Leg>>,
+  ) {
+    if stack.len() >= max_hops {
+      return;
+    }
+    for pool_id in pool_ids.iter() {
+      let pool = &self.pools[*pool_id];
+      let next_mint = if pool.mint_a == *mint {
+        pool.mint_b
+      } else if pool.mint_b == *mint {
+        pool.mint_a
+      } else {
+        continue;
+      };
+      let revisited = stack.iter().any(|(visited_pool, visited_mint, _)| {
+        visited_pool == *pool_id || *visited_mint == next_mint
+      });
+      if revisited {
+        continue;
+      }
+      stack.push((**pool_id, *mint, next_mint));
+      if next_mint == *ask_mint {
+        candidates.push(stack.clone());
+      } else {
+        self.search(&next_mint, ask_mint, max_hops, pool_ids, stack, candidates);
+      }
+      stack.pop();
+    }
+  }
+
+  ///
+  /// Quote a given sequence of legs
+  ///
+  pub fn quote(&self, legs: &[Leg], amount: u64) -> Option<Path> {
+    Self::simulate(&mut self.pools.clone(), legs, amount)
+  }
+
+  ///
+  /// Best single path up to max_hops
+  ///
+  pub fn best_path(
+    &self,
+    bid_mint: &Pubkey,
+    ask_mint: &Pubkey,
+    amount: u64,
+    max_hops: usize,
+  ) -> Option<Path> {
+    self
+      .legs(bid_mint, ask_mint, max_hops)
+      .iter()
+      .filter_map(|legs| self.quote(legs, amount))
+      .max_by_key(|path| path.ask_amount())
+  }
+
+  ///
+  /// Best split across up to max_paths paths, by allocating the amount in parts
+  /// to the path of the best marginal output. Paths are quoted sequentially
+  /// against the same pool states, exactly as split_route::exec executes them.
+  ///
+  pub fn best_split(
+    &self,
+    bid_mint: &Pubkey,
+    ask_mint: &Pubkey,
+    amount: u64,
+    max_hops: usize,
+    max_paths: usize,
+    parts: u64,
+  ) -> Option<Vec<Path>> {
+    let candidates = self.legs(bid_mint, ask_mint, max_hops);
+    let max_paths = max_paths.min(MAX_PATHS);
+    if candidates.is_empty() || max_paths == 0 || parts == 0 || amount < parts {
+      return None;
+    }
+    // Greedy allocation
+    let mut pools = self.pools.clone();
+    let mut allocations: Vec<(usize, u64)> = vec![];
+    let part = amount / parts;
+    for i in 0..parts {
+      let chunk = if i == parts - 1 {
+        amount - part * (parts - 1)
+      } else {
+        part
+      };
+      let best = candidates
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| {
+          allocations.len() < max_paths || allocations.iter().any(|(j, _)| j == index)
+        })
+        .filter_map(|(index, legs)| {
+          let path = Self::simulate(&mut pools.clone(), legs, chunk)?;
+          Some((index, path.ask_amount()))
+        })
+        .max_by_key(|(_, ask_amount)| *ask_amount);
+      let (index, _) = best?;
+      Self::simulate(&mut pools, &candidates[index], chunk)?;
+      match allocations.iter_mut().find(|(j, _)| *j == index) {
+        Some((_, allocated)) => *allocated += chunk,
+        None => allocations.push((index, chunk)),
+      }
+    }
+    // Exact sequential quote
+    let mut pools = self.pools.clone();
+    let split = allocations
+      .iter()
+      .map(|(index, allocated)| Self::simulate(&mut pools, &candidates[*index], *allocated))
+      .collect::<Option<Vec<Path>>>()?;
+    let split_ask_amount: u64 = split.iter().map(|path| path.ask_amount()).sum();
+    // Fall back to the single path if splitting doesn't help
+    let single = self.best_path(bid_mint, ask_mint, amount, max_hops)?;
+    if single.ask_amount() >= split_ask_amount {
+      return Some(vec![single]);
+    }
+    Some(split)
+  }
+
+  ///
+  /// Shared accounts of Route and SplitRoute
+  ///
+  fn shared_accounts(owner: &Pubkey) -> Vec<AccountMeta> {
+    vec![
+      AccountMeta::new(*owner, true),
+      AccountMeta::new_readonly(system_program::id(), false),
+      AccountMeta::new_readonly(spl_token::id(), false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+      AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+    ]
+  }
+
+  ///
+  /// The 8 detailed accounts of each hop
+  ///
+  pub fn hop_accounts(
+    &self,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    path: &Path,
+  ) -> Option<Vec<AccountMeta>> {
+    let mut accounts = vec![];
+    for hop in path.hops.iter() {
+      let pool = self.pools.get(&hop.pool)?;
+      let treasury_bid = Self::treasury(pool, &hop.bid_mint)?;
+      let treasury_ask = Self::treasury(pool, &hop.ask_mint)?;
+      let treasurer = Pubkey::create_program_address(&[&hop.pool.to_bytes()], program_id).ok()?;
+      accounts.append(&mut vec![
+        AccountMeta::new(hop.pool, false),
+        AccountMeta::new(get_associated_token_address(owner, &hop.bid_mint), false),
+        AccountMeta::new_readonly(hop.bid_mint, false),
+        AccountMeta::new(treasury_bid, false),
+        AccountMeta::new(get_associated_token_address(owner, &hop.ask_mint), false),
+        AccountMeta::new_readonly(hop.ask_mint, false),
+        AccountMeta::new(treasury_ask, false),
+        AccountMeta::new_readonly(treasurer, false),
+      ]);
+    }
+    Some(accounts)
+  }
+
+  fn treasury(pool: &Pool, mint: &Pubkey) -> Option<Pubkey> {
+    match pool.get_reserve(mint)? {
+      (0, _) => Some(pool.treasury_a),
+      _ => Some(pool.treasury_b),
+    }
+  }
+
+  ///
+  /// The exact account list route::exec expects
+  ///
+  pub fn route_accounts(
+    &self,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    path: &Path,
+  ) -> Option<Vec<AccountMeta>> {
+    let mut accounts = Self::shared_accounts(owner);
+    accounts.append(&mut self.hop_accounts(program_id, owner, path)?);
+    Some(accounts)
+  }
+
+  ///
+  /// The exact account list split_route::exec expects
+  ///
+  pub fn split_route_accounts(
+    &self,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    paths: &[Path],
+  ) -> Option<Vec<AccountMeta>> {
+    let mut accounts = Self::shared_accounts(owner);
+    for path in paths.iter() {
+      accounts.append(&mut self.hop_accounts(program_id, owner, path)?);
+    }
+    Some(accounts)
+  }
+
+  ///
+  /// Route instruction
+  ///
+  pub fn route_instruction(
+    &self,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    path: &Path,
+    limit: u64,
+  ) -> Option<Instruction> {
+    let mut data = vec![8];
+    data.extend_from_slice(&path.bid_amount().to_le_bytes());
+    data.extend_from_slice(&limit.to_le_bytes());
+    Some(Instruction {
+      program_id: *program_id,
+      accounts: self.route_accounts(program_id, owner, path)?,
+      data,
+    })
+  }
+
+  ///
+  /// SplitRoute instruction
+  ///
+  pub fn split_route_instruction(
+    &self,
+    program_id: &Pubkey,
+    owner: &Pubkey,
+    paths: &[Path],
+    limit: u64,
+  ) -> Option<Instruction> {
+    let mut data = vec![19];
+    data.extend_from_slice(&limit.to_le_bytes());
+    for path in paths.iter() {
+      data.extend_from_slice(&path.bid_amount().to_le_bytes());
+      data.push(path.hops.len() as u8);
+    }
+    Some(Instruction {
+      program_id: *program_id,
+      accounts: self.split_route_accounts(program_id, owner, paths)?,
+      data,
+    })
+  }
+}
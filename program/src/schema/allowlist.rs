@@ -0,0 +1,112 @@
+use crate::error::AppError;
+use crate::helper::math::PRECISION;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+  entrypoint::ProgramResult,
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack, Sealed},
+  pubkey::Pubkey,
+};
+
+///
+/// Maximum number of allowlisted members
+///
+pub const MAX_MEMBERS: usize = 16;
+
+///
+/// Allowlist struct (signers that skip the tax and pay a reduced fee). Members are signer
+/// keys or program IDs: a program matches when it invokes the swap straight from a transaction
+/// instruction, and the swap is given the instructions sysvar ahead of the allowlist.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Allowlist {
+  pub authority: Pubkey,
+  pub is_initialized: bool,
+  pub discount: u64,
+  pub n: u8,
+  pub members: [Pubkey; MAX_MEMBERS],
+}
+
+impl Allowlist {
+  ///
+  /// Replace the members and the fee discount (over PRECISION)
+  ///
+  pub fn configure(&mut self, discount: u64, members: &[Pubkey]) -> ProgramResult {
+    let n = members.len();
+    if n > MAX_MEMBERS || discount > PRECISION {
+      return Err(AppError::InvalidAllowlist.into());
+    }
+    self.discount = discount;
+    self.n = n as u8;
+    self.members = [Pubkey::new_from_array([0u8; 32]); MAX_MEMBERS];
+    self.members[..n].copy_from_slice(members);
+    Ok(())
+  }
+
+  ///
+  /// True if the key (a signer or a program ID) is allowlisted
+  ///
+  pub fn contains(&self, key: &Pubkey) -> bool {
+    self.members[..self.n as usize].contains(key)
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for Allowlist {}
+
+///
+/// IsInitialized trait
+///
+impl IsInitialized for Allowlist {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+
+///
+/// Pack trait
+///
+impl Pack for Allowlist {
+  // Fixed length
+  const LEN: usize = 554;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 554];
+    let (authority, is_initialized, discount, n, members_flat) =
+      array_refs![src, 32, 1, 8, 1, 32 * MAX_MEMBERS];
+    let mut result = Allowlist {
+      authority: Pubkey::new_from_array(*authority),
+      is_initialized: match is_initialized {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      discount: u64::from_le_bytes(*discount),
+      n: n[0],
+      members: [Pubkey::new_from_array([0u8; 32]); MAX_MEMBERS],
+    };
+    if result.n as usize > MAX_MEMBERS {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    for (i, dst) in result.members.iter_mut().enumerate() {
+      *dst = Pubkey::new_from_array(*array_ref![members_flat, 32 * i, 32]);
+    }
+    Ok(result)
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 554];
+    let (dst_authority, dst_is_initialized, dst_discount, dst_n, dst_members_flat) =
+      mut_array_refs![dst, 32, 1, 8, 1, 32 * MAX_MEMBERS];
+    dst_authority.copy_from_slice(self.authority.as_ref());
+    *dst_is_initialized = [self.is_initialized as u8];
+    *dst_discount = self.discount.to_le_bytes();
+    *dst_n = [self.n];
+    for (i, src) in self.members.iter().enumerate() {
+      let dst_array = array_mut_ref![dst_members_flat, 32 * i, 32];
+      dst_array.copy_from_slice(src.as_ref());
+    }
+  }
+}
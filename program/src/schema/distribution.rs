@@ -0,0 +1,149 @@
+use crate::error::AppError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use num_traits::ToPrimitive;
+use solana_program::{
+  entrypoint::ProgramResult,
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack, Sealed},
+  pubkey::Pubkey,
+};
+
+///
+/// Maximum number of tax recipients
+///
+pub const MAX_RECIPIENTS: usize = 8;
+///
+/// The shares of all recipients sum up to this
+///
+pub const BASIS_POINTS: u16 = 10000;
+
+///
+/// Distribution struct (a taxman that splits the tax among recipients)
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Distribution {
+  pub authority: Pubkey,
+  pub is_initialized: bool,
+  pub n: u8,
+  pub recipients: [Pubkey; MAX_RECIPIENTS],
+  pub shares: [u16; MAX_RECIPIENTS],
+}
+
+impl Distribution {
+  ///
+  /// Replace the table by the given recipients and basis-point shares
+  ///
+  pub fn configure(&mut self, recipients: &[Pubkey], shares: &[u16]) -> ProgramResult {
+    let n = recipients.len();
+    if n == 0 || n > MAX_RECIPIENTS || n != shares.len() {
+      return Err(AppError::InvalidDistribution.into());
+    }
+    if shares.contains(&0)
+      || shares.iter().map(|&share| share as u32).sum::<u32>() != BASIS_POINTS as u32
+    {
+      return Err(AppError::InvalidDistribution.into());
+    }
+    for (i, recipient) in recipients.iter().enumerate() {
+      if recipients[..i].contains(recipient) {
+        return Err(AppError::InvalidDistribution.into());
+      }
+    }
+    *self = Distribution {
+      authority: self.authority,
+      is_initialized: self.is_initialized,
+      n: n as u8,
+      ..Distribution::default()
+    };
+    self.recipients[..n].copy_from_slice(recipients);
+    self.shares[..n].copy_from_slice(shares);
+    Ok(())
+  }
+
+  ///
+  /// Split an amount by the shares. The rounding dust goes to the last recipient.
+  ///
+  pub fn split(&self, amount: u64) -> Option<Vec<u64>> {
+    let n = self.n as usize;
+    let mut amounts = Vec::with_capacity(n);
+    let mut remainder = amount;
+    for (i, &share) in self.shares[..n].iter().enumerate() {
+      let portion = if i == n - 1 {
+        remainder
+      } else {
+        amount
+          .to_u128()?
+          .checked_mul(share.to_u128()?)?
+          .checked_div(BASIS_POINTS.to_u128()?)?
+          .to_u64()?
+      };
+      remainder = remainder.checked_sub(portion)?;
+      amounts.push(portion);
+    }
+    Some(amounts)
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for Distribution {}
+
+///
+/// IsInitialized trait
+///
+impl IsInitialized for Distribution {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+
+///
+/// Pack trait
+///
+impl Pack for Distribution {
+  // Fixed length
+  const LEN: usize = 306;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 306];
+    let (authority, is_initialized, n, recipients_flat, shares_flat) =
+      array_refs![src, 32, 1, 1, 32 * MAX_RECIPIENTS, 2 * MAX_RECIPIENTS];
+    let mut result = Distribution {
+      authority: Pubkey::new_from_array(*authority),
+      is_initialized: match is_initialized {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      n: n[0],
+      recipients: [Pubkey::new_from_array([0u8; 32]); MAX_RECIPIENTS],
+      shares: [0; MAX_RECIPIENTS],
+    };
+    if result.n as usize > MAX_RECIPIENTS {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    for (i, dst) in result.recipients.iter_mut().enumerate() {
+      *dst = Pubkey::new_from_array(*array_ref![recipients_flat, 32 * i, 32]);
+    }
+    for (i, dst) in result.shares.iter_mut().enumerate() {
+      *dst = u16::from_le_bytes(*array_ref![shares_flat, 2 * i, 2]);
+    }
+    Ok(result)
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 306];
+    let (dst_authority, dst_is_initialized, dst_n, dst_recipients_flat, dst_shares_flat) =
+      mut_array_refs![dst, 32, 1, 1, 32 * MAX_RECIPIENTS, 2 * MAX_RECIPIENTS];
+    dst_authority.copy_from_slice(self.authority.as_ref());
+    *dst_is_initialized = [self.is_initialized as u8];
+    *dst_n = [self.n];
+    for (i, src) in self.recipients.iter().enumerate() {
+      let dst_array = array_mut_ref![dst_recipients_flat, 32 * i, 32];
+      dst_array.copy_from_slice(src.as_ref());
+    }
+    for (i, src) in self.shares.iter().enumerate() {
+      *array_mut_ref![dst_shares_flat, 2 * i, 2] = src.to_le_bytes();
+    }
+  }
+}
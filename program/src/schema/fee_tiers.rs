@@ -0,0 +1,142 @@
+use crate::error::AppError;
+use crate::helper::math::PRECISION;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+  entrypoint::ProgramResult,
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack, Sealed},
+  pubkey::Pubkey,
+};
+
+///
+/// Maximum number of fee tiers
+///
+pub const MAX_TIERS: usize = 4;
+
+///
+/// FeeTiers struct (fee discounts by governance token stake)
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FeeTiers {
+  pub authority: Pubkey,
+  pub is_initialized: bool,
+  pub mint: Pubkey,
+  pub n: u8,
+  pub thresholds: [u64; MAX_TIERS],
+  pub discounts: [u64; MAX_TIERS],
+}
+
+impl FeeTiers {
+  ///
+  /// Replace the table by the given (threshold, discount) tiers.
+  /// Thresholds must strictly increase and discounts (over PRECISION) must not decrease.
+  ///
+  pub fn configure(&mut self, tiers: &[(u64, u64)]) -> ProgramResult {
+    let n = tiers.len();
+    if n == 0 || n > MAX_TIERS {
+      return Err(AppError::InvalidFeeTiers.into());
+    }
+    if tiers
+      .iter()
+      .any(|&(threshold, discount)| threshold == 0 || discount == 0 || discount > PRECISION)
+    {
+      return Err(AppError::InvalidFeeTiers.into());
+    }
+    if tiers
+      .windows(2)
+      .any(|pair| pair[1].0 <= pair[0].0 || pair[1].1 < pair[0].1)
+    {
+      return Err(AppError::InvalidFeeTiers.into());
+    }
+    self.n = n as u8;
+    self.thresholds = [0; MAX_TIERS];
+    self.discounts = [0; MAX_TIERS];
+    for (i, &(threshold, discount)) in tiers.iter().enumerate() {
+      self.thresholds[i] = threshold;
+      self.discounts[i] = discount;
+    }
+    Ok(())
+  }
+
+  ///
+  /// Discount (over PRECISION) of the highest tier reached by the staked balance
+  ///
+  pub fn discount(&self, balance: u64) -> u64 {
+    let n = self.n as usize;
+    self.thresholds[..n]
+      .iter()
+      .zip(self.discounts[..n].iter())
+      .rev()
+      .find(|(&threshold, _)| balance >= threshold)
+      .map(|(_, &discount)| discount)
+      .unwrap_or(0)
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for FeeTiers {}
+
+///
+/// IsInitialized trait
+///
+impl IsInitialized for FeeTiers {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+
+///
+/// Pack trait
+///
+impl Pack for FeeTiers {
+  // Fixed length
+  const LEN: usize = 130;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 130];
+    let (authority, is_initialized, mint, n, thresholds_flat, discounts_flat) =
+      array_refs![src, 32, 1, 32, 1, 8 * MAX_TIERS, 8 * MAX_TIERS];
+    let mut result = FeeTiers {
+      authority: Pubkey::new_from_array(*authority),
+      is_initialized: match is_initialized {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      mint: Pubkey::new_from_array(*mint),
+      n: n[0],
+      thresholds: [0; MAX_TIERS],
+      discounts: [0; MAX_TIERS],
+    };
+    if result.n as usize > MAX_TIERS {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    for i in 0..MAX_TIERS {
+      result.thresholds[i] = u64::from_le_bytes(*array_ref![thresholds_flat, 8 * i, 8]);
+      result.discounts[i] = u64::from_le_bytes(*array_ref![discounts_flat, 8 * i, 8]);
+    }
+    Ok(result)
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 130];
+    let (
+      dst_authority,
+      dst_is_initialized,
+      dst_mint,
+      dst_n,
+      dst_thresholds_flat,
+      dst_discounts_flat,
+    ) = mut_array_refs![dst, 32, 1, 32, 1, 8 * MAX_TIERS, 8 * MAX_TIERS];
+    dst_authority.copy_from_slice(self.authority.as_ref());
+    *dst_is_initialized = [self.is_initialized as u8];
+    dst_mint.copy_from_slice(self.mint.as_ref());
+    *dst_n = [self.n];
+    for i in 0..MAX_TIERS {
+      *array_mut_ref![dst_thresholds_flat, 8 * i, 8] = self.thresholds[i].to_le_bytes();
+      *array_mut_ref![dst_discounts_flat, 8 * i, 8] = self.discounts[i].to_le_bytes();
+    }
+  }
+}
@@ -1,3 +1,11 @@
+pub mod allowlist;
+pub mod distribution;
+pub mod fee_tiers;
+pub mod multisig;
 pub mod pool;
 pub mod pool_state;
+pub mod pool_stats;
 pub mod pool_trait;
+pub mod pool_view;
+pub mod referrers;
+pub mod stake;
@@ -0,0 +1,105 @@
+use crate::error::AppError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+  account_info::AccountInfo,
+  entrypoint::ProgramResult,
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack, Sealed},
+  pubkey::Pubkey,
+};
+
+///
+/// Maximum number of multisig signers
+///
+pub const MAX_SIGNERS: usize = 11;
+
+///
+/// Multisig struct
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Multisig {
+  pub m: u8,
+  pub n: u8,
+  pub is_initialized: bool,
+  pub signers: [Pubkey; MAX_SIGNERS],
+}
+
+impl Multisig {
+  ///
+  /// Verify that at least m of the n registered signers signed
+  ///
+  pub fn is_approved(&self, signers: &[AccountInfo]) -> ProgramResult {
+    let mut matched = [false; MAX_SIGNERS];
+    let mut num_signers: u8 = 0;
+    for signer in signers.iter() {
+      for (position, key) in self.signers[0..self.n as usize].iter().enumerate() {
+        if key == signer.key && !matched[position] {
+          if !signer.is_signer {
+            return Err(AppError::InvalidOwner.into());
+          }
+          matched[position] = true;
+          num_signers += 1;
+          break;
+        }
+      }
+    }
+    if num_signers < self.m {
+      return Err(AppError::NotEnoughSigners.into());
+    }
+    Ok(())
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for Multisig {}
+
+///
+/// IsInitialized trait
+///
+impl IsInitialized for Multisig {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+
+///
+/// Pack trait
+///
+impl Pack for Multisig {
+  // Fixed length
+  const LEN: usize = 355;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 355];
+    let (m, n, is_initialized, signers_flat) = array_refs![src, 1, 1, 1, 32 * MAX_SIGNERS];
+    let mut result = Multisig {
+      m: m[0],
+      n: n[0],
+      is_initialized: match is_initialized {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      signers: [Pubkey::new_from_array([0u8; 32]); MAX_SIGNERS],
+    };
+    for (i, dst) in result.signers.iter_mut().enumerate() {
+      *dst = Pubkey::new_from_array(*array_ref![signers_flat, 32 * i, 32]);
+    }
+    Ok(result)
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 355];
+    let (dst_m, dst_n, dst_is_initialized, dst_signers_flat) =
+      mut_array_refs![dst, 1, 1, 1, 32 * MAX_SIGNERS];
+    *dst_m = [self.m];
+    *dst_n = [self.n];
+    *dst_is_initialized = [self.is_initialized as u8];
+    for (i, src) in self.signers.iter().enumerate() {
+      let dst_array = array_mut_ref![dst_signers_flat, 32 * i, 32];
+      dst_array.copy_from_slice(src.as_ref());
+    }
+  }
+}
@@ -1,7 +1,7 @@
 use crate::error::AppError;
-use crate::helper::math::{U128Roots, PRECISION};
 use crate::schema::{
-  pool_state::PoolState,
+  pool_state::{PoolState, PAUSE_ALL},
+  pool_stats::PoolStats,
   pool_trait::{Exchange, Operation},
 };
 use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
@@ -9,12 +9,34 @@ use num_enum::TryFromPrimitive;
 use num_traits::ToPrimitive;
 use solana_program::{
   entrypoint::ProgramResult,
-  msg,
   program_error::ProgramError,
   program_pack::{IsInitialized, Pack, Sealed},
   pubkey::Pubkey,
 };
 
+///
+/// Fee bounds (over PRECISION)
+///
+pub const MAX_FEE_RATIO: u64 = 100000000; // 10%
+pub const MAX_TAX_RATIO: u64 = 500000000; // 50%
+///
+/// Referral bound (over PRECISION, as a share of the fee)
+///
+pub const MAX_REFERRAL_RATIO: u64 = 500000000; // 50%
+
+///
+/// Layout version written by this program
+///
+pub const POOL_VERSION: u8 = 1;
+///
+/// Length of the original, unversioned layout
+///
+pub const LEGACY_POOL_LEN: usize = 257;
+///
+/// Bytes kept free at the end of the pool for future fields
+///
+pub const POOL_RESERVED: usize = 96;
+
 ///
 /// Just take the correct ratio of tokens
 /// Return the rest
@@ -44,6 +66,16 @@ pub fn extract(a: u64, b: u64, reserve_a: u64, reserve_b: u64) -> Option<(u64, u
   }
 }
 
+///
+/// Signed difference between the actual treasury balances and the recorded reserves
+///
+pub fn drift(balance_a: u64, balance_b: u64, reserve_a: u64, reserve_b: u64) -> (i128, i128) {
+  (
+    balance_a as i128 - reserve_a as i128,
+    balance_b as i128 - reserve_b as i128,
+  )
+}
+
 ///
 /// Pool struct
 ///
@@ -64,14 +96,93 @@ pub struct Pool {
 
   pub fee_ratio: u64,
   pub tax_ratio: u64,
+
+  pub pauser: Pubkey,
+  pub fee_manager: Pubkey,
+  pub paused: u8,
+  pub locked: bool,
+
+  pub tax_a: u64,
+  pub tax_b: u64,
+
+  pub max_referral_ratio: u64,
+  pub fee_tiers: Pubkey,
+  pub allowlist: Pubkey,
+  pub referrers: Pubkey,
+
+  pub stats: PoolStats,
+}
+
+impl Pool {
+  ///
+  /// Decode the original, unversioned layout. The roles that came later default to the owner.
+  ///
+  pub fn unpack_legacy(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 257];
+    let (
+      owner,
+      state,
+      mint_lpt,
+      taxman,
+      mint_a,
+      treasury_a,
+      reserve_a,
+      mint_b,
+      treasury_b,
+      reserve_b,
+      fee_ratio,
+      tax_ratio,
+    ) = array_refs![src, 32, 1, 32, 32, 32, 32, 8, 32, 32, 8, 8, 8];
+    let owner = Pubkey::new_from_array(*owner);
+    Ok(Pool {
+      owner,
+      state: PoolState::try_from_primitive(state[0]).or(Err(ProgramError::InvalidAccountData))?,
+      mint_lpt: Pubkey::new_from_array(*mint_lpt),
+      taxman: Pubkey::new_from_array(*taxman),
+      mint_a: Pubkey::new_from_array(*mint_a),
+      treasury_a: Pubkey::new_from_array(*treasury_a),
+      reserve_a: u64::from_le_bytes(*reserve_a),
+      mint_b: Pubkey::new_from_array(*mint_b),
+      treasury_b: Pubkey::new_from_array(*treasury_b),
+      reserve_b: u64::from_le_bytes(*reserve_b),
+      fee_ratio: u64::from_le_bytes(*fee_ratio),
+      tax_ratio: u64::from_le_bytes(*tax_ratio),
+      pauser: owner,
+      fee_manager: owner,
+      ..Pool::default()
+    })
+  }
 }
 
 ///
 /// Operation trait
 ///
 impl Operation for Pool {
-  fn is_frozen(&self) -> bool {
-    self.state == PoolState::Frozen
+  fn is_paused(&self, flag: u8) -> bool {
+    self.state == PoolState::Frozen || self.paused & flag != 0
+  }
+  fn pause(&mut self, mask: u8) {
+    self.paused |= mask;
+  }
+  fn unpause(&mut self, mask: u8) {
+    // Convert the legacy frozen state to flags
+    if self.state == PoolState::Frozen {
+      self.state = PoolState::Initialized;
+      self.paused = PAUSE_ALL;
+    }
+    self.paused &= !mask;
+  }
+  fn is_unlocked(&self) -> ProgramResult {
+    if self.locked {
+      return Err(AppError::LockedPool.into());
+    }
+    Ok(())
+  }
+  fn lock(&mut self) {
+    self.locked = true;
+  }
+  fn unlock(&mut self) {
+    self.locked = false;
   }
   fn is_owner(&self, expected_owner: Pubkey) -> ProgramResult {
     if self.owner != expected_owner {
@@ -79,6 +190,24 @@ impl Operation for Pool {
     }
     Ok(())
   }
+  fn is_pauser(&self, expected_pauser: Pubkey) -> ProgramResult {
+    if self.pauser != expected_pauser {
+      return Err(AppError::InvalidOwner.into());
+    }
+    Ok(())
+  }
+  fn is_fee_manager(&self, expected_fee_manager: Pubkey) -> ProgramResult {
+    if self.fee_manager != expected_fee_manager {
+      return Err(AppError::InvalidOwner.into());
+    }
+    Ok(())
+  }
+  fn is_valid_ratio(fee_ratio: u64, tax_ratio: u64) -> ProgramResult {
+    if fee_ratio > MAX_FEE_RATIO || tax_ratio > MAX_TAX_RATIO {
+      return Err(AppError::InvalidRatio.into());
+    }
+    Ok(())
+  }
 }
 
 ///
@@ -95,92 +224,21 @@ impl Exchange for Pool {
     None
   }
 
-  fn curve(
-    &self,
-    bid_amount: u64,
-    bid_mint: &Pubkey,
-    ask_mint: &Pubkey,
-  ) -> Option<(u64, u64, u64)> {
-    let (_, bid_reserve) = self.get_reserve(bid_mint)?;
-    let (_, ask_reserve) = self.get_reserve(ask_mint)?;
-    let new_bid_reserve = bid_reserve.checked_add(bid_amount)?;
-    let new_ask_reserve = bid_reserve
-      .to_u128()?
-      .checked_mul(ask_reserve.to_u128()?)?
-      .checked_div(new_bid_reserve.to_u128()?)?
-      .to_u64()?;
-    let ask_amount = ask_reserve.checked_sub(new_ask_reserve)?;
-    Some((ask_amount, new_bid_reserve, new_ask_reserve))
+  fn reserves(&self) -> (u64, u64) {
+    (self.reserve_a, self.reserve_b)
   }
 
-  fn fee(&self, ask_amount: u64) -> Option<(u64, u64, u64)> {
-    let fee = self
-      .fee_ratio
-      .to_u128()?
-      .checked_mul(ask_amount.to_u128()?)?
-      .checked_div(PRECISION.to_u128()?)?
-      .to_u64()?;
-    let temp_amount = ask_amount.checked_sub(fee)?;
-    let tax = self
-      .tax_ratio
-      .to_u128()?
-      .checked_mul(temp_amount.to_u128()?)?
-      .checked_div(PRECISION.to_u128()?)?
-      .to_u64()?;
-    let amount = temp_amount.checked_sub(tax)?;
-    Some((amount, fee, tax))
+  fn ratios(&self) -> (u64, u64, u64) {
+    (self.fee_ratio, self.tax_ratio, self.max_referral_ratio)
   }
 
-  fn deposit(
-    &self,
-    delta_a: u64,
-    delta_b: u64,
-    liquidity: u64,
-  ) -> Option<(u64, u64, u64, u64, u64, u64)> {
-    // The pool hasn't initialized the reserves
-    if self.reserve_a == 0 && self.reserve_b == 0 {
-      let lpt = delta_a
-        .to_u128()?
-        .checked_mul(delta_b.to_u128()?)?
-        .sqrt()
-        .to_u64()?;
-      return Some((delta_a, delta_b, lpt, delta_a, delta_b, lpt));
+  fn accrue_tax(&mut self, mint: &Pubkey, tax: u64) -> Option<()> {
+    let (code, _) = self.get_reserve(mint)?;
+    match code {
+      0 => self.tax_a = self.tax_a.checked_add(tax)?,
+      _ => self.tax_b = self.tax_b.checked_add(tax)?,
     }
-    // The pool of non-empty reserves
-    let (a, b) = extract(delta_a, delta_b, self.reserve_a, self.reserve_b)?;
-    let new_reserve_a = a.checked_add(self.reserve_a)?;
-    let new_reserve_b = b.checked_add(self.reserve_b)?;
-    let lpt = a
-      .to_u128()?
-      .checked_mul(liquidity.to_u128()?)?
-      .checked_div(self.reserve_a.to_u128()?)?
-      .to_u64()?;
-    let new_liquidity = liquidity.checked_add(lpt)?;
-    Some((a, b, lpt, new_reserve_a, new_reserve_b, new_liquidity))
-  }
-
-  fn withdraw(&self, lpt: u64, liquidity: u64) -> Option<(u64, u64, u64, u64, u64, u64)> {
-    let new_liquidity = liquidity.checked_sub(lpt)?;
-    let new_reserve_a = new_liquidity
-      .to_u128()?
-      .checked_mul(self.reserve_a.to_u128()?)?
-      .checked_div(liquidity.to_u128()?)?
-      .to_u64()?;
-    let new_reserve_b = new_liquidity
-      .to_u128()?
-      .checked_mul(self.reserve_b.to_u128()?)?
-      .checked_div(liquidity.to_u128()?)?
-      .to_u64()?;
-    let delta_a = self.reserve_a.checked_sub(new_reserve_a)?;
-    let delta_b = self.reserve_b.checked_sub(new_reserve_b)?;
-    Some((
-      delta_a,
-      delta_b,
-      lpt,
-      new_reserve_a,
-      new_reserve_b,
-      new_liquidity,
-    ))
+    Some(())
   }
 }
 
@@ -203,11 +261,10 @@ impl IsInitialized for Pool {
 ///
 impl Pack for Pool {
   // Fixed length
-  const LEN: usize = 257;
+  const LEN: usize = 724;
   // Unpack data from [u8] to the data struct
   fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-    msg!("Read pool data");
-    let src = array_ref![src, 0, 257];
+    let src = array_ref![src, 0, 724];
     let (
       owner,
       state,
@@ -221,7 +278,52 @@ impl Pack for Pool {
       reserve_b,
       fee_ratio,
       tax_ratio,
-    ) = array_refs![src, 32, 1, 32, 32, 32, 32, 8, 32, 32, 8, 8, 8];
+      pauser,
+      fee_manager,
+      paused,
+      locked,
+      tax_a,
+      tax_b,
+      max_referral_ratio,
+      fee_tiers,
+      allowlist,
+      stats,
+      version,
+      referrers,
+      _reserved,
+    ) = array_refs![
+      src,
+      32,
+      1,
+      32,
+      32,
+      32,
+      32,
+      8,
+      32,
+      32,
+      8,
+      8,
+      8,
+      32,
+      32,
+      1,
+      1,
+      8,
+      8,
+      8,
+      32,
+      32,
+      184,
+      1,
+      32,
+      POOL_RESERVED
+    ];
+    // A freshly rented account is all zeros
+    let is_uninitialized = state[0] == PoolState::Uninitialized as u8;
+    if version[0] != POOL_VERSION && !(version[0] == 0 && is_uninitialized) {
+      return Err(ProgramError::InvalidAccountData);
+    }
     Ok(Pool {
       owner: Pubkey::new_from_array(*owner),
       state: PoolState::try_from_primitive(state[0]).or(Err(ProgramError::InvalidAccountData))?,
@@ -235,12 +337,26 @@ impl Pack for Pool {
       reserve_b: u64::from_le_bytes(*reserve_b),
       fee_ratio: u64::from_le_bytes(*fee_ratio),
       tax_ratio: u64::from_le_bytes(*tax_ratio),
+      pauser: Pubkey::new_from_array(*pauser),
+      fee_manager: Pubkey::new_from_array(*fee_manager),
+      paused: paused[0],
+      locked: match locked {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      tax_a: u64::from_le_bytes(*tax_a),
+      tax_b: u64::from_le_bytes(*tax_b),
+      max_referral_ratio: u64::from_le_bytes(*max_referral_ratio),
+      fee_tiers: Pubkey::new_from_array(*fee_tiers),
+      allowlist: Pubkey::new_from_array(*allowlist),
+      referrers: Pubkey::new_from_array(*referrers),
+      stats: PoolStats::unpack_from_slice(stats)?,
     })
   }
   // Pack data from the data struct to [u8]
   fn pack_into_slice(&self, dst: &mut [u8]) {
-    msg!("Write pool data");
-    let dst = array_mut_ref![dst, 0, 257];
+    let dst = array_mut_ref![dst, 0, 724];
     let (
       dst_owner,
       dst_state,
@@ -254,7 +370,47 @@ impl Pack for Pool {
       dst_reserve_b,
       dst_fee_ratio,
       dst_tax_ratio,
-    ) = mut_array_refs![dst, 32, 1, 32, 32, 32, 32, 8, 32, 32, 8, 8, 8];
+      dst_pauser,
+      dst_fee_manager,
+      dst_paused,
+      dst_locked,
+      dst_tax_a,
+      dst_tax_b,
+      dst_max_referral_ratio,
+      dst_fee_tiers,
+      dst_allowlist,
+      dst_stats,
+      dst_version,
+      dst_referrers,
+      dst_reserved,
+    ) = mut_array_refs![
+      dst,
+      32,
+      1,
+      32,
+      32,
+      32,
+      32,
+      8,
+      32,
+      32,
+      8,
+      8,
+      8,
+      32,
+      32,
+      1,
+      1,
+      8,
+      8,
+      8,
+      32,
+      32,
+      184,
+      1,
+      32,
+      POOL_RESERVED
+    ];
     let &Pool {
       ref owner,
       state,
@@ -268,6 +424,17 @@ impl Pack for Pool {
       reserve_b,
       fee_ratio,
       tax_ratio,
+      ref pauser,
+      ref fee_manager,
+      paused,
+      locked,
+      tax_a,
+      tax_b,
+      max_referral_ratio,
+      ref fee_tiers,
+      ref allowlist,
+      ref referrers,
+      ref stats,
     } = self;
     dst_owner.copy_from_slice(owner.as_ref());
     *dst_state = [state as u8];
@@ -281,5 +448,18 @@ impl Pack for Pool {
     *dst_reserve_b = reserve_b.to_le_bytes();
     *dst_fee_ratio = fee_ratio.to_le_bytes();
     *dst_tax_ratio = tax_ratio.to_le_bytes();
+    dst_pauser.copy_from_slice(pauser.as_ref());
+    dst_fee_manager.copy_from_slice(fee_manager.as_ref());
+    *dst_paused = [paused];
+    *dst_locked = [locked as u8];
+    *dst_tax_a = tax_a.to_le_bytes();
+    *dst_tax_b = tax_b.to_le_bytes();
+    *dst_max_referral_ratio = max_referral_ratio.to_le_bytes();
+    dst_fee_tiers.copy_from_slice(fee_tiers.as_ref());
+    dst_allowlist.copy_from_slice(allowlist.as_ref());
+    stats.pack_into_slice(dst_stats);
+    *dst_version = [POOL_VERSION];
+    dst_referrers.copy_from_slice(referrers.as_ref());
+    *dst_reserved = [0; POOL_RESERVED];
   }
 }
@@ -1,7 +1,16 @@
 use num_enum::TryFromPrimitive;
 
+///
+/// Pause flags
+///
+pub const PAUSE_SWAP: u8 = 1;
+pub const PAUSE_DEPOSIT: u8 = 2;
+pub const PAUSE_WITHDRAW: u8 = 4;
+pub const PAUSE_ALL: u8 = PAUSE_SWAP | PAUSE_DEPOSIT | PAUSE_WITHDRAW;
+
 ///
 /// Pool state
+/// (Frozen is the legacy all-paused state, pools are now paused by flags)
 ///
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, TryFromPrimitive)]
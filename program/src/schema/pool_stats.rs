@@ -0,0 +1,140 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+  program_error::ProgramError,
+  program_pack::{Pack, Sealed},
+};
+
+///
+/// Bytes kept free at the end of the stats for future counters
+///
+pub const STATS_RESERVED: usize = 64;
+
+///
+/// Cumulative pool statistics
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PoolStats {
+  // Swapped in and out, in each token
+  pub volume_a: u128,
+  pub volume_b: u128,
+  // Fee earned by the LPs, in each token
+  pub fee_a: u128,
+  pub fee_b: u128,
+  // Tax paid, in each token
+  pub tax_a: u128,
+  pub tax_b: u128,
+  pub swaps: u64,
+  pub last_trade_slot: u64,
+  // Last slot of any swap or liquidity change
+  pub last_update_slot: u64,
+}
+
+impl PoolStats {
+  ///
+  /// Record a swap of (code, amount) where code is 0 for A, 1 for B
+  ///
+  pub fn record_swap(&mut self, bid: (u8, u64), ask: (u8, u64), fee: u64, tax: u64, slot: u64) {
+    let (ask_code, _) = ask;
+    for &(code, amount) in [bid, ask].iter() {
+      match code {
+        0 => self.volume_a = self.volume_a.saturating_add(amount as u128),
+        _ => self.volume_b = self.volume_b.saturating_add(amount as u128),
+      }
+    }
+    match ask_code {
+      0 => {
+        self.fee_a = self.fee_a.saturating_add(fee as u128);
+        self.tax_a = self.tax_a.saturating_add(tax as u128);
+      }
+      _ => {
+        self.fee_b = self.fee_b.saturating_add(fee as u128);
+        self.tax_b = self.tax_b.saturating_add(tax as u128);
+      }
+    }
+    self.swaps = self.swaps.saturating_add(1);
+    self.last_trade_slot = slot;
+    self.last_update_slot = slot;
+  }
+
+  ///
+  /// Record a liquidity change
+  ///
+  pub fn record_liquidity(&mut self, slot: u64) {
+    self.last_update_slot = slot;
+  }
+
+  ///
+  /// Record a fee that isn't part of a swap (e.g. flash loans)
+  ///
+  pub fn record_fee(&mut self, fee_a: u64, fee_b: u64, slot: u64) {
+    self.fee_a = self.fee_a.saturating_add(fee_a as u128);
+    self.fee_b = self.fee_b.saturating_add(fee_b as u128);
+    self.last_update_slot = slot;
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for PoolStats {}
+
+///
+/// Pack trait
+///
+impl Pack for PoolStats {
+  // Fixed length
+  const LEN: usize = 184;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 184];
+    let (
+      volume_a,
+      volume_b,
+      fee_a,
+      fee_b,
+      tax_a,
+      tax_b,
+      swaps,
+      last_trade_slot,
+      last_update_slot,
+      _reserved,
+    ) = array_refs![src, 16, 16, 16, 16, 16, 16, 8, 8, 8, STATS_RESERVED];
+    Ok(PoolStats {
+      volume_a: u128::from_le_bytes(*volume_a),
+      volume_b: u128::from_le_bytes(*volume_b),
+      fee_a: u128::from_le_bytes(*fee_a),
+      fee_b: u128::from_le_bytes(*fee_b),
+      tax_a: u128::from_le_bytes(*tax_a),
+      tax_b: u128::from_le_bytes(*tax_b),
+      swaps: u64::from_le_bytes(*swaps),
+      last_trade_slot: u64::from_le_bytes(*last_trade_slot),
+      last_update_slot: u64::from_le_bytes(*last_update_slot),
+    })
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 184];
+    let (
+      dst_volume_a,
+      dst_volume_b,
+      dst_fee_a,
+      dst_fee_b,
+      dst_tax_a,
+      dst_tax_b,
+      dst_swaps,
+      dst_last_trade_slot,
+      dst_last_update_slot,
+      dst_reserved,
+    ) = mut_array_refs![dst, 16, 16, 16, 16, 16, 16, 8, 8, 8, STATS_RESERVED];
+    *dst_volume_a = self.volume_a.to_le_bytes();
+    *dst_volume_b = self.volume_b.to_le_bytes();
+    *dst_fee_a = self.fee_a.to_le_bytes();
+    *dst_fee_b = self.fee_b.to_le_bytes();
+    *dst_tax_a = self.tax_a.to_le_bytes();
+    *dst_tax_b = self.tax_b.to_le_bytes();
+    *dst_swaps = self.swaps.to_le_bytes();
+    *dst_last_trade_slot = self.last_trade_slot.to_le_bytes();
+    *dst_last_update_slot = self.last_update_slot.to_le_bytes();
+    *dst_reserved = [0; STATS_RESERVED];
+  }
+}
@@ -1,33 +1,179 @@
+use crate::helper::math::{U128Roots, PRECISION};
+use crate::schema::pool::extract;
+use num_traits::ToPrimitive;
 use solana_program::{entrypoint::ProgramResult, pubkey::Pubkey};
 
 ///
 /// Pool operation trait
 ///
 pub trait Operation {
-  // True if frozen else False
-  fn is_frozen(&self) -> bool;
+  // True if any of the flags is paused else False
+  fn is_paused(&self, flag: u8) -> bool;
+  // Pause the flags in mask
+  fn pause(&mut self, mask: u8);
+  // Unpause the flags in mask
+  fn unpause(&mut self, mask: u8);
+  // Verify the pool is not locked by an ongoing instruction
+  fn is_unlocked(&self) -> ProgramResult;
+  // Lock the pool against reentrancy
+  fn lock(&mut self);
+  // Unlock the pool
+  fn unlock(&mut self);
   // Verify pool owner
   fn is_owner(&self, expected_owner: Pubkey) -> ProgramResult;
+  // Verify pool pauser (freeze only)
+  fn is_pauser(&self, expected_pauser: Pubkey) -> ProgramResult;
+  // Verify pool fee manager (fee/tax updates)
+  fn is_fee_manager(&self, expected_fee_manager: Pubkey) -> ProgramResult;
+  // Verify fee/tax ratios are within bounds
+  fn is_valid_ratio(fee_ratio: u64, tax_ratio: u64) -> ProgramResult;
 }
 
 ///
-/// Pool exchange trait
+/// Pool exchange trait. The math is shared by the implementors, which only
+/// give access to the reserves and ratios.
 ///
 pub trait Exchange {
-  // Get code () for A, 1 for B) and reserve
+  // Get code (0 for A, 1 for B) and reserve
   fn get_reserve(&self, mint: &Pubkey) -> Option<(u8, u64)>;
+  // Reserves of A and B
+  fn reserves(&self) -> (u64, u64);
+  // Fee, tax and referral cap ratios
+  fn ratios(&self) -> (u64, u64, u64);
+  // Accrue tax on the given mint, to be claimed by the taxman
+  fn accrue_tax(&mut self, mint: &Pubkey, tax: u64) -> Option<()>;
+
   // Pricing curve
-  fn curve(&self, bid_amount: u64, bid_mint: &Pubkey, ask_mint: &Pubkey)
-    -> Option<(u64, u64, u64)>;
+  fn curve(
+    &self,
+    bid_amount: u64,
+    bid_mint: &Pubkey,
+    ask_mint: &Pubkey,
+  ) -> Option<(u64, u64, u64)> {
+    let (_, bid_reserve) = self.get_reserve(bid_mint)?;
+    let (_, ask_reserve) = self.get_reserve(ask_mint)?;
+    let new_bid_reserve = bid_reserve.checked_add(bid_amount)?;
+    let new_ask_reserve = bid_reserve
+      .to_u128()?
+      .checked_mul(ask_reserve.to_u128()?)?
+      .checked_div(new_bid_reserve.to_u128()?)?
+      .to_u64()?;
+    let ask_amount = ask_reserve.checked_sub(new_ask_reserve)?;
+    Some((ask_amount, new_bid_reserve, new_ask_reserve))
+  }
+
   // Fee
-  fn fee(&self, ask_amount: u64) -> Option<(u64, u64, u64)>;
+  fn fee(&self, ask_amount: u64) -> Option<(u64, u64, u64)> {
+    self.discounted_fee(ask_amount, 0, false)
+  }
+
+  // Fee with a discount (over PRECISION) on the fee ratio, and optionally without tax
+  fn discounted_fee(
+    &self,
+    ask_amount: u64,
+    discount: u64,
+    tax_exempt: bool,
+  ) -> Option<(u64, u64, u64)> {
+    let (fee_ratio, tax_ratio, _) = self.ratios();
+    let fee_ratio = fee_ratio
+      .to_u128()?
+      .checked_mul(PRECISION.checked_sub(discount)?.to_u128()?)?
+      .checked_div(PRECISION.to_u128()?)?;
+    let fee = fee_ratio
+      .checked_mul(ask_amount.to_u128()?)?
+      .checked_div(PRECISION.to_u128()?)?
+      .to_u64()?;
+    let temp_amount = ask_amount.checked_sub(fee)?;
+    let tax_ratio = if tax_exempt { 0 } else { tax_ratio };
+    let tax = tax_ratio
+      .to_u128()?
+      .checked_mul(temp_amount.to_u128()?)?
+      .checked_div(PRECISION.to_u128()?)?
+      .to_u64()?;
+    let amount = temp_amount.checked_sub(tax)?;
+    Some((amount, fee, tax))
+  }
+
+  // Flash loan fee
+  fn flash_fee(&self, amount: u64) -> Option<u64> {
+    // Round up so that small loans are never free
+    let (fee_ratio, _, _) = self.ratios();
+    let fee = fee_ratio
+      .to_u128()?
+      .checked_mul(amount.to_u128()?)?
+      .checked_add(PRECISION.to_u128()?.checked_sub(1)?)?
+      .checked_div(PRECISION.to_u128()?)?
+      .to_u64()?;
+    Some(fee)
+  }
+
+  // Referrer's slice of the fee (None if the ratio exceeds the pool's cap)
+  fn referral(&self, fee: u64, referral_ratio: u64) -> Option<u64> {
+    let (_, _, max_referral_ratio) = self.ratios();
+    if referral_ratio > max_referral_ratio {
+      return None;
+    }
+    let referral = referral_ratio
+      .to_u128()?
+      .checked_mul(fee.to_u128()?)?
+      .checked_div(PRECISION.to_u128()?)?
+      .to_u64()?;
+    Some(referral)
+  }
+
   // Add liquidity
   fn deposit(
     &self,
     delta_a: u64,
     delta_b: u64,
     liquidity: u64,
-  ) -> Option<(u64, u64, u64, u64, u64, u64)>;
+  ) -> Option<(u64, u64, u64, u64, u64, u64)> {
+    let (reserve_a, reserve_b) = self.reserves();
+    // The pool hasn't initialized the reserves
+    if reserve_a == 0 && reserve_b == 0 {
+      let lpt = delta_a
+        .to_u128()?
+        .checked_mul(delta_b.to_u128()?)?
+        .sqrt()
+        .to_u64()?;
+      return Some((delta_a, delta_b, lpt, delta_a, delta_b, lpt));
+    }
+    // The pool of non-empty reserves
+    let (a, b) = extract(delta_a, delta_b, reserve_a, reserve_b)?;
+    let new_reserve_a = a.checked_add(reserve_a)?;
+    let new_reserve_b = b.checked_add(reserve_b)?;
+    let lpt = a
+      .to_u128()?
+      .checked_mul(liquidity.to_u128()?)?
+      .checked_div(reserve_a.to_u128()?)?
+      .to_u64()?;
+    let new_liquidity = liquidity.checked_add(lpt)?;
+    Some((a, b, lpt, new_reserve_a, new_reserve_b, new_liquidity))
+  }
+
   // Remove liquidity
-  fn withdraw(&self, lpt: u64, liquidity: u64) -> Option<(u64, u64, u64, u64, u64, u64)>;
+  fn withdraw(&self, lpt: u64, liquidity: u64) -> Option<(u64, u64, u64, u64, u64, u64)> {
+    let (reserve_a, reserve_b) = self.reserves();
+    let new_liquidity = liquidity.checked_sub(lpt)?;
+    let new_reserve_a = new_liquidity
+      .to_u128()?
+      .checked_mul(reserve_a.to_u128()?)?
+      .checked_div(liquidity.to_u128()?)?
+      .to_u64()?;
+    let new_reserve_b = new_liquidity
+      .to_u128()?
+      .checked_mul(reserve_b.to_u128()?)?
+      .checked_div(liquidity.to_u128()?)?
+      .to_u64()?;
+    let delta_a = reserve_a.checked_sub(new_reserve_a)?;
+    let delta_b = reserve_b.checked_sub(new_reserve_b)?;
+    Some((
+      delta_a,
+      delta_b,
+      lpt,
+      new_reserve_a,
+      new_reserve_b,
+      new_liquidity,
+    ))
+  }
 }
@@ -0,0 +1,209 @@
+use crate::error::AppError;
+use crate::schema::{
+  pool::{Pool, POOL_RESERVED, POOL_VERSION},
+  pool_state::{PoolState, PAUSE_ALL},
+  pool_stats::PoolStats,
+  pool_trait::{Exchange, Operation},
+};
+use bytemuck::{Pod, Zeroable};
+use solana_program::{
+  entrypoint::ProgramResult, program_error::ProgramError, program_pack::Pack, pubkey::Pubkey,
+};
+
+///
+/// Zero-copy view over the pool account data, field for field with the Pack layout.
+/// Every field is a byte array so the struct has no padding and can be cast in place.
+///
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct PoolView {
+  pub owner: [u8; 32],
+  pub state: u8,
+  pub mint_lpt: [u8; 32],
+  pub taxman: [u8; 32],
+
+  pub mint_a: [u8; 32],
+  pub treasury_a: [u8; 32],
+  pub reserve_a: [u8; 8],
+
+  pub mint_b: [u8; 32],
+  pub treasury_b: [u8; 32],
+  pub reserve_b: [u8; 8],
+
+  pub fee_ratio: [u8; 8],
+  pub tax_ratio: [u8; 8],
+
+  pub pauser: [u8; 32],
+  pub fee_manager: [u8; 32],
+  pub paused: u8,
+  pub locked: u8,
+
+  pub tax_a: [u8; 8],
+  pub tax_b: [u8; 8],
+
+  pub max_referral_ratio: [u8; 8],
+  pub fee_tiers: [u8; 32],
+  pub allowlist: [u8; 32],
+
+  pub stats: [u8; PoolStats::LEN],
+
+  pub version: u8,
+  pub referrers: [u8; 32],
+  pub reserved: [u8; POOL_RESERVED],
+}
+
+// The view must cover exactly the packed pool
+const _: [(); Pool::LEN] = [(); std::mem::size_of::<PoolView>()];
+
+impl PoolView {
+  ///
+  /// Borrow the view of an initialized pool
+  ///
+  pub fn load(data: &[u8]) -> Result<&PoolView, ProgramError> {
+    let view: &PoolView = bytemuck::try_from_bytes(
+      data
+        .get(..Pool::LEN)
+        .ok_or(ProgramError::InvalidAccountData)?,
+    )
+    .or(Err(ProgramError::InvalidAccountData))?;
+    view.is_valid()?;
+    Ok(view)
+  }
+
+  ///
+  /// Mutably borrow the view of an initialized pool
+  ///
+  pub fn load_mut(data: &mut [u8]) -> Result<&mut PoolView, ProgramError> {
+    let view: &mut PoolView = bytemuck::try_from_bytes_mut(
+      data
+        .get_mut(..Pool::LEN)
+        .ok_or(ProgramError::InvalidAccountData)?,
+    )
+    .or(Err(ProgramError::InvalidAccountData))?;
+    view.is_valid()?;
+    Ok(view)
+  }
+
+  fn is_valid(&self) -> ProgramResult {
+    if self.version != POOL_VERSION || self.locked > 1 {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    if self.state == 0 {
+      return Err(ProgramError::UninitializedAccount);
+    }
+    Ok(())
+  }
+
+  ///
+  /// Set the reserve by code (0 for A, 1 for B)
+  ///
+  pub fn set_reserve(&mut self, code: u8, reserve: u64) -> ProgramResult {
+    match code {
+      0 => self.reserve_a = reserve.to_le_bytes(),
+      1 => self.reserve_b = reserve.to_le_bytes(),
+      _ => return Err(AppError::UnmatchedPool.into()),
+    }
+    Ok(())
+  }
+
+  pub fn stats(&self) -> Result<PoolStats, ProgramError> {
+    PoolStats::unpack_from_slice(&self.stats)
+  }
+
+  pub fn set_stats(&mut self, stats: &PoolStats) {
+    stats.pack_into_slice(&mut self.stats);
+  }
+}
+
+///
+/// Operation trait
+///
+impl Operation for PoolView {
+  fn is_paused(&self, flag: u8) -> bool {
+    self.state == PoolState::Frozen as u8 || self.paused & flag != 0
+  }
+  fn pause(&mut self, mask: u8) {
+    self.paused |= mask;
+  }
+  fn unpause(&mut self, mask: u8) {
+    // Convert the legacy frozen state to flags
+    if self.state == PoolState::Frozen as u8 {
+      self.state = PoolState::Initialized as u8;
+      self.paused = PAUSE_ALL;
+    }
+    self.paused &= !mask;
+  }
+  fn is_unlocked(&self) -> ProgramResult {
+    if self.locked != 0 {
+      return Err(AppError::LockedPool.into());
+    }
+    Ok(())
+  }
+  fn lock(&mut self) {
+    self.locked = 1;
+  }
+  fn unlock(&mut self) {
+    self.locked = 0;
+  }
+  fn is_owner(&self, expected_owner: Pubkey) -> ProgramResult {
+    if self.owner != expected_owner.to_bytes() {
+      return Err(AppError::InvalidOwner.into());
+    }
+    Ok(())
+  }
+  fn is_pauser(&self, expected_pauser: Pubkey) -> ProgramResult {
+    if self.pauser != expected_pauser.to_bytes() {
+      return Err(AppError::InvalidOwner.into());
+    }
+    Ok(())
+  }
+  fn is_fee_manager(&self, expected_fee_manager: Pubkey) -> ProgramResult {
+    if self.fee_manager != expected_fee_manager.to_bytes() {
+      return Err(AppError::InvalidOwner.into());
+    }
+    Ok(())
+  }
+  fn is_valid_ratio(fee_ratio: u64, tax_ratio: u64) -> ProgramResult {
+    Pool::is_valid_ratio(fee_ratio, tax_ratio)
+  }
+}
+
+///
+/// Exchange trait
+///
+impl Exchange for PoolView {
+  fn get_reserve(&self, mint: &Pubkey) -> Option<(u8, u64)> {
+    if mint.as_ref() == self.mint_a {
+      Some((0, u64::from_le_bytes(self.reserve_a)))
+    } else if mint.as_ref() == self.mint_b {
+      Some((1, u64::from_le_bytes(self.reserve_b)))
+    } else {
+      None
+    }
+  }
+
+  fn reserves(&self) -> (u64, u64) {
+    (
+      u64::from_le_bytes(self.reserve_a),
+      u64::from_le_bytes(self.reserve_b),
+    )
+  }
+
+  fn ratios(&self) -> (u64, u64, u64) {
+    (
+      u64::from_le_bytes(self.fee_ratio),
+      u64::from_le_bytes(self.tax_ratio),
+      u64::from_le_bytes(self.max_referral_ratio),
+    )
+  }
+
+  fn accrue_tax(&mut self, mint: &Pubkey, tax: u64) -> Option<()> {
+    let (code, _) = self.get_reserve(mint)?;
+    let dst = match code {
+      0 => &mut self.tax_a,
+      _ => &mut self.tax_b,
+    };
+    *dst = u64::from_le_bytes(*dst).checked_add(tax)?.to_le_bytes();
+    Some(())
+  }
+}
@@ -0,0 +1,105 @@
+use crate::error::AppError;
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+  entrypoint::ProgramResult,
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack, Sealed},
+  pubkey::Pubkey,
+};
+
+///
+/// Maximum number of registered referrers
+///
+pub const MAX_REFERRERS: usize = 16;
+
+///
+/// Referrers struct (wallets whose token accounts may take a referral of the pool)
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Referrers {
+  pub authority: Pubkey,
+  pub is_initialized: bool,
+  pub n: u8,
+  pub members: [Pubkey; MAX_REFERRERS],
+}
+
+impl Referrers {
+  ///
+  /// Replace the registered referrers
+  ///
+  pub fn configure(&mut self, members: &[Pubkey]) -> ProgramResult {
+    let n = members.len();
+    if n > MAX_REFERRERS {
+      return Err(AppError::InvalidReferrers.into());
+    }
+    self.n = n as u8;
+    self.members = [Pubkey::new_from_array([0u8; 32]); MAX_REFERRERS];
+    self.members[..n].copy_from_slice(members);
+    Ok(())
+  }
+
+  ///
+  /// True if the wallet is registered
+  ///
+  pub fn contains(&self, key: &Pubkey) -> bool {
+    self.members[..self.n as usize].contains(key)
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for Referrers {}
+
+///
+/// IsInitialized trait
+///
+impl IsInitialized for Referrers {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+
+///
+/// Pack trait
+///
+impl Pack for Referrers {
+  // Fixed length
+  const LEN: usize = 546;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 546];
+    let (authority, is_initialized, n, members_flat) =
+      array_refs![src, 32, 1, 1, 32 * MAX_REFERRERS];
+    let mut result = Referrers {
+      authority: Pubkey::new_from_array(*authority),
+      is_initialized: match is_initialized {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      n: n[0],
+      members: [Pubkey::new_from_array([0u8; 32]); MAX_REFERRERS],
+    };
+    if result.n as usize > MAX_REFERRERS {
+      return Err(ProgramError::InvalidAccountData);
+    }
+    for (i, dst) in result.members.iter_mut().enumerate() {
+      *dst = Pubkey::new_from_array(*array_ref![members_flat, 32 * i, 32]);
+    }
+    Ok(result)
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 546];
+    let (dst_authority, dst_is_initialized, dst_n, dst_members_flat) =
+      mut_array_refs![dst, 32, 1, 1, 32 * MAX_REFERRERS];
+    dst_authority.copy_from_slice(self.authority.as_ref());
+    *dst_is_initialized = [self.is_initialized as u8];
+    *dst_n = [self.n];
+    for (i, src) in self.members.iter().enumerate() {
+      let dst_array = array_mut_ref![dst_members_flat, 32 * i, 32];
+      dst_array.copy_from_slice(src.as_ref());
+    }
+  }
+}
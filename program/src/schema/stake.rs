@@ -0,0 +1,89 @@
+use arrayref::{array_mut_ref, array_ref, array_refs, mut_array_refs};
+use solana_program::{
+  program_error::ProgramError,
+  program_pack::{IsInitialized, Pack, Sealed},
+  pubkey::Pubkey,
+};
+
+///
+/// Slots the last deposit must be held before the stake counts toward a fee tier (about a day)
+///
+pub const HOLDING_SLOTS: u64 = 216_000;
+
+///
+/// Stake struct (governance tokens locked in the program's vault for the fee tiers)
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stake {
+  pub owner: Pubkey,
+  pub is_initialized: bool,
+  pub mint: Pubkey,
+  pub vault: Pubkey,
+  pub amount: u64,
+  pub staked_at: u64,
+}
+
+impl Stake {
+  ///
+  /// Amount that counts toward a fee tier at the slot. Nothing counts until the last deposit
+  /// was held for HOLDING_SLOTS, so tokens borrowed or moved within a transaction never do.
+  ///
+  pub fn holding(&self, slot: u64) -> u64 {
+    match slot.checked_sub(self.staked_at) {
+      Some(held) if held >= HOLDING_SLOTS => self.amount,
+      _ => 0,
+    }
+  }
+}
+
+///
+/// Sealed trait
+///
+impl Sealed for Stake {}
+
+///
+/// IsInitialized trait
+///
+impl IsInitialized for Stake {
+  fn is_initialized(&self) -> bool {
+    self.is_initialized
+  }
+}
+
+///
+/// Pack trait
+///
+impl Pack for Stake {
+  // Fixed length
+  const LEN: usize = 113;
+  // Unpack data from [u8] to the data struct
+  fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+    let src = array_ref![src, 0, 113];
+    let (owner, is_initialized, mint, vault, amount, staked_at) =
+      array_refs![src, 32, 1, 32, 32, 8, 8];
+    Ok(Stake {
+      owner: Pubkey::new_from_array(*owner),
+      is_initialized: match is_initialized {
+        [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidAccountData),
+      },
+      mint: Pubkey::new_from_array(*mint),
+      vault: Pubkey::new_from_array(*vault),
+      amount: u64::from_le_bytes(*amount),
+      staked_at: u64::from_le_bytes(*staked_at),
+    })
+  }
+  // Pack data from the data struct to [u8]
+  fn pack_into_slice(&self, dst: &mut [u8]) {
+    let dst = array_mut_ref![dst, 0, 113];
+    let (dst_owner, dst_is_initialized, dst_mint, dst_vault, dst_amount, dst_staked_at) =
+      mut_array_refs![dst, 32, 1, 32, 32, 8, 8];
+    dst_owner.copy_from_slice(self.owner.as_ref());
+    *dst_is_initialized = [self.is_initialized as u8];
+    dst_mint.copy_from_slice(self.mint.as_ref());
+    dst_vault.copy_from_slice(self.vault.as_ref());
+    *dst_amount = self.amount.to_le_bytes();
+    *dst_staked_at = self.staked_at.to_le_bytes();
+  }
+}
@@ -0,0 +1,140 @@
+use main::{
+  processor::add_sided_liquidity::split,
+  schema::{
+    pool::{extract, Pool},
+    pool_state::PoolState,
+    pool_trait::Exchange,
+  },
+};
+use solana_program::pubkey::Pubkey;
+
+fn pool(reserve_a: u64, reserve_b: u64, fee_ratio: u64, tax_ratio: u64) -> Pool {
+  Pool {
+    state: PoolState::Initialized,
+    mint_a: Pubkey::new_unique(),
+    reserve_a,
+    mint_b: Pubkey::new_unique(),
+    reserve_b,
+    fee_ratio,
+    tax_ratio,
+    ..Pool::default()
+  }
+}
+
+///
+/// Swap bid_amount of A to B, then deposit the rest of A with the received B.
+/// Return the leftover valued in A.
+///
+fn dust(pool: &Pool, amount: u64, bid_amount: u64) -> u64 {
+  let (temp_ask_amount, new_reserve_a, temp_new_reserve_b) =
+    pool.curve(bid_amount, &pool.mint_a, &pool.mint_b).unwrap();
+  let (ask_amount, fee, _) = pool.fee(temp_ask_amount).unwrap();
+  let new_reserve_b = temp_new_reserve_b + fee;
+  let a = amount - bid_amount;
+  let (a_star, b_star) = extract(a, ask_amount, new_reserve_a, new_reserve_b).unwrap();
+  let leftover_a = a - a_star;
+  let leftover_b = ask_amount - b_star;
+  leftover_a + (leftover_b as u128 * new_reserve_a as u128 / new_reserve_b as u128) as u64
+}
+
+const CASES: [(u64, u64, u64, u64, u64); 10] = [
+  // (reserve_a, reserve_b, fee_ratio, tax_ratio, amount)
+  (1_000_000, 1_000_000, 0, 0, 1_000),
+  (1_000_000, 1_000_000, 2500000, 0, 500_000),
+  (1_000_000_000, 5_000_000_000, 2500000, 500000, 10_000_000),
+  (
+    1_000_000_000_000,
+    1_000_000,
+    3000000,
+    100000000,
+    2_000_000_000_000,
+  ),
+  (
+    5_000_000_000_000_000,
+    7_000_000_000_000_000,
+    2500000,
+    500000,
+    1_000_000_000_000_000,
+  ),
+  (
+    10_000,
+    1_000_000_000_000_000,
+    100000000,
+    500000000,
+    1_000_000,
+  ),
+  (u64::MAX / 4, u64::MAX / 4, 2500000, 500000, u64::MAX / 8),
+  (1_000_000_000, 1_000_000_000, 2500000, 0, 1),
+  // A small amount against a large reserve
+  (
+    1_000_000_000_000_000_000,
+    1_000_000_000_000_000_000,
+    2500000,
+    500000,
+    100_000_000,
+  ),
+  (
+    1_000_000_000_000_000_000,
+    2_000_000_000,
+    2500000,
+    500000,
+    10_000_000_000_000,
+  ),
+];
+
+#[test]
+fn split_without_fee_is_the_constant_product_root() {
+  // s = sqrt(reserve^2 + amount*reserve) - reserve
+  let bid_amount = split(3_000_000, 1_000_000, 0, 0).unwrap();
+  assert_eq!(bid_amount, 1_000_000);
+}
+
+#[test]
+fn split_leaves_minimal_dust() {
+  for &(reserve_a, reserve_b, fee_ratio, tax_ratio, amount) in CASES.iter() {
+    let pool = pool(reserve_a, reserve_b, fee_ratio, tax_ratio);
+    let bid_amount = split(amount, reserve_a, fee_ratio, tax_ratio).unwrap();
+    assert!(bid_amount <= amount);
+    // Worth no more than ten units of the dearer side
+    let bound = 10 * (reserve_a / reserve_b + 1);
+    assert!(dust(&pool, amount, bid_amount) <= bound);
+  }
+}
+
+#[test]
+fn split_is_locally_optimal() {
+  for &(reserve_a, reserve_b, fee_ratio, tax_ratio, amount) in CASES.iter() {
+    let pool = pool(reserve_a, reserve_b, fee_ratio, tax_ratio);
+    let bid_amount = split(amount, reserve_a, fee_ratio, tax_ratio).unwrap();
+    let best = dust(&pool, amount, bid_amount);
+    // Tolerance of the rounding of the deposit
+    let tolerance = 4;
+    for delta in [1, 10, 100, amount / 1000, amount / 100].iter() {
+      if *delta == 0 {
+        continue;
+      }
+      if bid_amount + delta < amount {
+        assert!(best <= dust(&pool, amount, bid_amount + delta) + tolerance);
+      }
+      if bid_amount >= *delta {
+        assert!(best <= dust(&pool, amount, bid_amount - delta) + tolerance);
+      }
+    }
+  }
+}
+
+#[test]
+fn split_of_a_small_amount_against_a_large_reserve() {
+  let reserve = 1_000_000_000_000_000_000;
+  // About half of the amount, not lost to a scaling of the inputs
+  let bid_amount = split(1 << 27, reserve, 2500000, 500000).unwrap();
+  assert!(bid_amount > 0);
+  assert!(bid_amount.max(1 << 26) - bid_amount.min(1 << 26) < (1 << 26) / 100);
+  // A single unit is not worth a swap
+  assert_eq!(split(1, reserve, 2500000, 500000), Some(0));
+}
+
+#[test]
+fn split_of_an_empty_reserve() {
+  assert_eq!(split(1_000, 0, 2500000, 0), None);
+}
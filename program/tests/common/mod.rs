@@ -0,0 +1,1349 @@
+// Each test crate uses a different subset of the harness
+#![allow(dead_code)]
+
+use main::{
+  error::AppError,
+  helper::pubutil::Boolean,
+  processor::Processor,
+  schema::{pool::Pool, pool_view::PoolView},
+};
+use solana_program_runtime::invoke_context::BuiltinFunctionWithContext;
+use solana_program_test::{
+  processor, BanksClientError, BanksTransactionResultWithMetadata, ProgramTest, ProgramTestContext,
+};
+use solana_sdk::{
+  account::Account as SolanaAccount,
+  account_info::AccountInfo,
+  clock::Clock,
+  entrypoint::ProgramResult,
+  instruction::{AccountMeta, Instruction, InstructionError},
+  program::invoke,
+  program_pack::Pack,
+  pubkey::Pubkey,
+  rent::Rent,
+  signature::{Keypair, Signature, Signer},
+  system_instruction, system_program, sysvar,
+  transaction::{Transaction, TransactionError},
+};
+use spl_associated_token_account::get_associated_token_address;
+use spl_token::state::{Account, Mint};
+use std::{collections::HashSet, convert::TryInto};
+
+///
+/// Compute unit limit of every transaction
+///
+pub const MAX_UNITS: u64 = 1_400_000;
+///
+/// Lamports given to every new wallet
+///
+pub const WALLET_LAMPORTS: u64 = 10_000_000_000;
+
+///
+/// Addresses of an initialized pool
+///
+#[derive(Clone, Copy, Debug)]
+pub struct PoolKeys {
+  pub pool: Pubkey,
+  pub treasurer: Pubkey,
+  pub mint_lpt: Pubkey,
+  pub mint_a: Pubkey,
+  pub treasury_a: Pubkey,
+  pub mint_b: Pubkey,
+  pub treasury_b: Pubkey,
+}
+
+impl PoolKeys {
+  pub fn new(
+    program_id: &Pubkey,
+    pool: Pubkey,
+    mint_lpt: Pubkey,
+    mint_a: Pubkey,
+    mint_b: Pubkey,
+  ) -> Self {
+    let treasurer = Pubkey::create_program_address(&[&pool.to_bytes()], program_id).unwrap();
+    PoolKeys {
+      pool,
+      treasurer,
+      mint_lpt,
+      mint_a,
+      treasury_a: get_associated_token_address(&treasurer, &mint_a),
+      mint_b,
+      treasury_b: get_associated_token_address(&treasurer, &mint_b),
+    }
+  }
+
+  pub fn treasury(&self, mint: &Pubkey) -> Pubkey {
+    if *mint == self.mint_a {
+      self.treasury_a
+    } else {
+      self.treasury_b
+    }
+  }
+}
+
+///
+/// A flash loan borrower. The accounts are the token program, the owner,
+/// then a (src, dst) pair for each little-endian u64 amount of the data.
+///
+pub fn repay(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+  let (splt_program, rest) = accounts.split_first().unwrap();
+  let (owner, pairs) = rest.split_first().unwrap();
+  for (pair, amount) in pairs.chunks(2).zip(data.chunks(8)) {
+    let amount = u64::from_le_bytes(amount.try_into().unwrap());
+    if amount == 0 {
+      continue;
+    }
+    let ix = spl_token::instruction::transfer(
+      splt_program.key,
+      pair[0].key,
+      pair[1].key,
+      owner.key,
+      &[],
+      amount,
+    )?;
+    invoke(
+      &ix,
+      &[
+        pair[0].clone(),
+        pair[1].clone(),
+        owner.clone(),
+        splt_program.clone(),
+      ],
+    )?;
+  }
+  Ok(())
+}
+
+///
+/// A partner program. It forwards its instruction data to the program of the first account,
+/// with the rest of the accounts.
+///
+pub fn forward(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+  let (target, rest) = accounts.split_first().unwrap();
+  let ix = Instruction {
+    program_id: *target.key,
+    accounts: rest
+      .iter()
+      .map(|acc| AccountMeta {
+        pubkey: *acc.key,
+        is_signer: acc.is_signer,
+        is_writable: acc.is_writable,
+      })
+      .collect(),
+    data: data.to_vec(),
+  };
+  invoke(&ix, accounts)
+}
+
+///
+/// A local bank with the program, SPL Token, the associated token program, a borrower
+/// and a partner
+///
+pub struct Env {
+  pub context: ProgramTestContext,
+  pub program_id: Pubkey,
+  pub borrower_id: Pubkey,
+  pub partner_id: Pubkey,
+  // Signatures sent under the current blockhash
+  sent: HashSet<Signature>,
+}
+
+impl Env {
+  pub async fn start() -> Env {
+    Self::start_with(|_, _| {}).await
+  }
+
+  ///
+  /// Start with extra accounts added by the configure closure (given the program id)
+  ///
+  pub async fn start_with<F: FnOnce(&mut ProgramTest, &Pubkey)>(configure: F) -> Env {
+    let program_id = Pubkey::new_unique();
+    let borrower_id = Pubkey::new_unique();
+    let partner_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::new("main", program_id, processor!(Processor::process));
+    // The borrower and the partner have no BPF build, so they stay native under cargo test-sbf too
+    let borrower: Option<BuiltinFunctionWithContext> = processor!(repay);
+    program_test.add_builtin_program("borrower", borrower_id, borrower.unwrap());
+    let partner: Option<BuiltinFunctionWithContext> = processor!(forward);
+    program_test.add_builtin_program("partner", partner_id, partner.unwrap());
+    program_test.set_compute_max_units(MAX_UNITS);
+    configure(&mut program_test, &program_id);
+    Env {
+      context: program_test.start_with_context().await,
+      program_id,
+      borrower_id,
+      partner_id,
+      sent: HashSet::new(),
+    }
+  }
+
+  pub fn payer(&self) -> Pubkey {
+    self.context.payer.pubkey()
+  }
+
+  ///
+  /// Sign a transaction paid by the payer. A repeated transaction would be rejected as
+  /// already processed so it waits for a new blockhash.
+  ///
+  async fn transaction(
+    &mut self,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+  ) -> Transaction {
+    loop {
+      let payer = self.payer();
+      let mut all_signers = vec![&self.context.payer];
+      all_signers.extend(signers.iter().filter(|signer| signer.pubkey() != payer));
+      let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&payer),
+        &all_signers,
+        self.context.last_blockhash,
+      );
+      if self.sent.insert(transaction.signatures[0]) {
+        return transaction;
+      }
+      self.context.get_new_latest_blockhash().await.unwrap();
+      self.sent.clear();
+    }
+  }
+
+  ///
+  /// Process the instructions in one transaction paid by the payer
+  ///
+  pub async fn process(
+    &mut self,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+  ) -> Result<(), BanksClientError> {
+    let transaction = self.transaction(instructions, signers).await;
+    self
+      .context
+      .banks_client
+      .process_transaction(transaction)
+      .await
+  }
+
+  ///
+  /// Process the instructions and return the compute units consumed
+  ///
+  pub async fn process_with_units(
+    &mut self,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+  ) -> Result<u64, TransactionError> {
+    let result = self.process_with_metadata(instructions, signers).await;
+    result.result?;
+    Ok(result.metadata.unwrap().compute_units_consumed)
+  }
+
+  ///
+  /// Process the instructions and return the transaction return data (empty if none)
+  ///
+  pub async fn process_with_return_data(
+    &mut self,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+  ) -> Result<Vec<u8>, TransactionError> {
+    let result = self.process_with_metadata(instructions, signers).await;
+    result.result?;
+    Ok(
+      result
+        .metadata
+        .unwrap()
+        .return_data
+        .map(|return_data| return_data.data)
+        .unwrap_or_default(),
+    )
+  }
+
+  async fn process_with_metadata(
+    &mut self,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+  ) -> BanksTransactionResultWithMetadata {
+    let transaction = self.transaction(instructions, signers).await;
+    self
+      .context
+      .banks_client
+      .process_transaction_with_metadata(transaction)
+      .await
+      .unwrap()
+  }
+
+  ///
+  /// Move the bank forward by the given number of slots
+  ///
+  pub async fn warp(&mut self, slots: u64) {
+    let clock: Clock = self.context.banks_client.get_sysvar().await.unwrap();
+    self.context.warp_to_slot(clock.slot + slots).unwrap();
+    self.context.get_new_latest_blockhash().await.unwrap();
+    self.sent.clear();
+  }
+
+  ///
+  /// A funded wallet
+  ///
+  pub async fn create_wallet(&mut self) -> Keypair {
+    let wallet = Keypair::new();
+    let ix = system_instruction::transfer(&self.payer(), &wallet.pubkey(), WALLET_LAMPORTS);
+    self.process(&[ix], &[]).await.unwrap();
+    wallet
+  }
+
+  ///
+  /// A 9-decimal mint whose authority is the payer
+  ///
+  pub async fn create_mint(&mut self) -> Pubkey {
+    let mint = Keypair::new();
+    let rent = self.context.banks_client.get_rent().await.unwrap();
+    let ixs = [
+      system_instruction::create_account(
+        &self.payer(),
+        &mint.pubkey(),
+        rent.minimum_balance(Mint::LEN),
+        Mint::LEN as u64,
+        &spl_token::id(),
+      ),
+      spl_token::instruction::initialize_mint(
+        &spl_token::id(),
+        &mint.pubkey(),
+        &self.payer(),
+        None,
+        9,
+      )
+      .unwrap(),
+    ];
+    self.process(&ixs, &[&mint]).await.unwrap();
+    mint.pubkey()
+  }
+
+  ///
+  /// The associated token account of the wallet, created if missing
+  ///
+  pub async fn create_ata(&mut self, wallet: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let ata = get_associated_token_address(wallet, mint);
+    if self.account_exists(&ata).await {
+      return ata;
+    }
+    let ix = create_ata(&self.payer(), wallet, mint);
+    self.process(&[ix], &[]).await.unwrap();
+    ata
+  }
+
+  ///
+  /// Mint tokens to the associated token account of the wallet
+  ///
+  pub async fn mint_to(&mut self, wallet: &Pubkey, mint: &Pubkey, amount: u64) -> Pubkey {
+    let ata = self.create_ata(wallet, mint).await;
+    let ix =
+      spl_token::instruction::mint_to(&spl_token::id(), mint, &ata, &self.payer(), &[], amount)
+        .unwrap();
+    self.process(&[ix], &[]).await.unwrap();
+    ata
+  }
+
+  pub async fn account_exists(&mut self, address: &Pubkey) -> bool {
+    self
+      .context
+      .banks_client
+      .get_account(*address)
+      .await
+      .unwrap()
+      .is_some()
+  }
+
+  ///
+  /// Token balance of an account (0 if missing)
+  ///
+  pub async fn token_balance(&mut self, address: &Pubkey) -> u64 {
+    match self
+      .context
+      .banks_client
+      .get_account(*address)
+      .await
+      .unwrap()
+    {
+      Some(account) => Account::unpack(&account.data).unwrap().amount,
+      None => 0,
+    }
+  }
+
+  ///
+  /// Token balance of the wallet's associated account (0 if missing)
+  ///
+  pub async fn balance(&mut self, wallet: &Pubkey, mint: &Pubkey) -> u64 {
+    self
+      .token_balance(&get_associated_token_address(wallet, mint))
+      .await
+  }
+
+  pub async fn supply(&mut self, mint: &Pubkey) -> u64 {
+    let account = self.context.banks_client.get_account(*mint).await.unwrap();
+    Mint::unpack(&account.unwrap().data).unwrap().supply
+  }
+
+  ///
+  /// Decoded pool state
+  ///
+  pub async fn pool(&mut self, pool: &Pubkey) -> Pool {
+    let account = self.context.banks_client.get_account(*pool).await.unwrap();
+    let data = account.unwrap().data;
+    // The zero-copy view must agree with Pack
+    PoolView::load(&data).unwrap();
+    Pool::unpack(&data).unwrap()
+  }
+
+  ///
+  /// Initialize a pool owned and funded by the payer
+  ///
+  pub async fn create_pool(
+    &mut self,
+    mint_a: &Pubkey,
+    mint_b: &Pubkey,
+    delta_a: u64,
+    delta_b: u64,
+    fee_ratio: u64,
+    tax_ratio: u64,
+  ) -> PoolKeys {
+    let payer = self.payer();
+    self.mint_to(&payer, mint_a, delta_a).await;
+    self.mint_to(&payer, mint_b, delta_b).await;
+    let (pool, mint_lpt) = (pool_keypair(&self.program_id), Keypair::new());
+    let keys = PoolKeys::new(
+      &self.program_id,
+      pool.pubkey(),
+      mint_lpt.pubkey(),
+      *mint_a,
+      *mint_b,
+    );
+    let ix = initialize_pool(
+      &self.program_id,
+      &payer,
+      &payer,
+      &payer,
+      &keys,
+      delta_a,
+      delta_b,
+      fee_ratio,
+      tax_ratio,
+    );
+    self.process(&[ix], &[&pool, &mint_lpt]).await.unwrap();
+    keys
+  }
+}
+
+///
+/// A bank with an A/B pool owned by the payer, and a trader holding both tokens
+///
+pub async fn setup(
+  reserve_a: u64,
+  reserve_b: u64,
+  fee_ratio: u64,
+  tax_ratio: u64,
+) -> (Env, PoolKeys, Keypair) {
+  let mut env = Env::start().await;
+  let mint_a = env.create_mint().await;
+  let mint_b = env.create_mint().await;
+  let keys = env
+    .create_pool(&mint_a, &mint_b, reserve_a, reserve_b, fee_ratio, tax_ratio)
+    .await;
+  let trader = env.create_wallet().await;
+  env.mint_to(&trader.pubkey(), &mint_a, reserve_a).await;
+  env.mint_to(&trader.pubkey(), &mint_b, reserve_b).await;
+  (env, keys, trader)
+}
+
+///
+/// A program-owned account holding the packed pool, to be added before the bank starts
+///
+pub fn pool_account(program_id: &Pubkey, pool: &Pool) -> SolanaAccount {
+  let mut data = vec![0; Pool::LEN];
+  Pool::pack(*pool, &mut data).unwrap();
+  SolanaAccount {
+    lamports: Rent::default().minimum_balance(Pool::LEN),
+    data,
+    owner: *program_id,
+    executable: false,
+    rent_epoch: 0,
+  }
+}
+
+///
+/// Pool (and stake) addresses are chosen so that the treasurer exists
+///
+pub fn pool_keypair(program_id: &Pubkey) -> Keypair {
+  loop {
+    let pool = Keypair::new();
+    if Pubkey::create_program_address(&[&pool.pubkey().to_bytes()], program_id).is_ok() {
+      return pool;
+    }
+  }
+}
+
+///
+/// Assert a transaction failed with the app error
+///
+pub fn assert_app_error(result: Result<(), BanksClientError>, error: AppError) {
+  match result.expect_err("the transaction should fail").unwrap() {
+    TransactionError::InstructionError(_, InstructionError::Custom(code)) => {
+      assert_eq!(code, error as u32)
+    }
+    other => panic!("expected {:?}, got {:?}", error, other),
+  }
+}
+
+///
+/// Create an associated token account (the program has no instruction builder)
+///
+pub fn create_ata(payer: &Pubkey, wallet: &Pubkey, mint: &Pubkey) -> Instruction {
+  Instruction {
+    program_id: spl_associated_token_account::id(),
+    accounts: vec![
+      AccountMeta::new(*payer, true),
+      AccountMeta::new(get_associated_token_address(wallet, mint), false),
+      AccountMeta::new_readonly(*wallet, false),
+      AccountMeta::new_readonly(*mint, false),
+      AccountMeta::new_readonly(system_program::id(), false),
+      AccountMeta::new_readonly(spl_token::id(), false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ],
+    data: vec![],
+  }
+}
+
+fn data(tag: u8, values: &[u64]) -> Vec<u8> {
+  let mut data = vec![tag];
+  for value in values.iter() {
+    data.extend_from_slice(&value.to_le_bytes());
+  }
+  data
+}
+
+///
+/// Signer accounts that follow an authority (multisig members)
+///
+fn signer_accounts(signers: &[Pubkey]) -> Vec<AccountMeta> {
+  signers
+    .iter()
+    .map(|signer| AccountMeta::new_readonly(*signer, true))
+    .collect()
+}
+
+///
+/// treasurer, system, splt, rent and splata
+///
+fn program_accounts(treasurer: &Pubkey) -> Vec<AccountMeta> {
+  vec![
+    AccountMeta::new_readonly(*treasurer, false),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(spl_token::id(), false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+    AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+  ]
+}
+
+///
+/// The (token account, mint, treasury) triples of both sides
+///
+fn pair_accounts(wallet: &Pubkey, keys: &PoolKeys) -> Vec<AccountMeta> {
+  vec![
+    AccountMeta::new(get_associated_token_address(wallet, &keys.mint_a), false),
+    AccountMeta::new_readonly(keys.mint_a, false),
+    AccountMeta::new(keys.treasury_a, false),
+    AccountMeta::new(get_associated_token_address(wallet, &keys.mint_b), false),
+    AccountMeta::new_readonly(keys.mint_b, false),
+    AccountMeta::new(keys.treasury_b, false),
+  ]
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn initialize_pool(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  owner: &Pubkey,
+  taxman: &Pubkey,
+  keys: &PoolKeys,
+  delta_a: u64,
+  delta_b: u64,
+  fee_ratio: u64,
+  tax_ratio: u64,
+) -> Instruction {
+  let proof = program_id.xor(&keys.pool.xor(&keys.treasurer));
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*owner, false),
+    AccountMeta::new(keys.pool, true),
+    AccountMeta::new(get_associated_token_address(payer, &keys.mint_lpt), false),
+    AccountMeta::new(keys.mint_lpt, true),
+    AccountMeta::new_readonly(*taxman, false),
+    AccountMeta::new_readonly(proof, false),
+  ];
+  accounts.append(&mut pair_accounts(payer, keys));
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: data(0, &[delta_a, delta_b, fee_ratio, tax_ratio]),
+  }
+}
+
+///
+/// The accounts shared by AddLiquidity, AddSidedLiquidity, RemoveLiquidity and EmergencyWithdraw
+///
+fn liquidity(program_id: &Pubkey, owner: &Pubkey, keys: &PoolKeys, data: Vec<u8>) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*owner, true),
+    AccountMeta::new(keys.pool, false),
+    AccountMeta::new(get_associated_token_address(owner, &keys.mint_lpt), false),
+    AccountMeta::new(keys.mint_lpt, false),
+  ];
+  accounts.append(&mut pair_accounts(owner, keys));
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+pub fn add_liquidity(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  delta_a: u64,
+  delta_b: u64,
+) -> Instruction {
+  liquidity(program_id, owner, keys, data(1, &[delta_a, delta_b]))
+}
+
+pub fn remove_liquidity(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  lpt: u64,
+) -> Instruction {
+  liquidity(program_id, owner, keys, data(2, &[lpt]))
+}
+
+pub fn add_sided_liquidity(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  delta_a: u64,
+  delta_b: u64,
+) -> Instruction {
+  liquidity(program_id, owner, keys, data(10, &[delta_a, delta_b]))
+}
+
+pub fn emergency_withdraw(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  lpt: u64,
+) -> Instruction {
+  liquidity(program_id, owner, keys, data(14, &[lpt]))
+}
+
+///
+/// Swap, optionally paying a referrer (the associated account of the ask mint) registered
+/// in the pool's referrers
+///
+#[allow(clippy::too_many_arguments)]
+pub fn swap(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  bid_mint: &Pubkey,
+  ask_mint: &Pubkey,
+  amount: u64,
+  limit: u64,
+  referral: Option<(&Pubkey, &Pubkey, u64)>,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*owner, true),
+    AccountMeta::new(keys.pool, false),
+    AccountMeta::new(get_associated_token_address(owner, bid_mint), false),
+    AccountMeta::new_readonly(*bid_mint, false),
+    AccountMeta::new(keys.treasury(bid_mint), false),
+    AccountMeta::new(get_associated_token_address(owner, ask_mint), false),
+    AccountMeta::new_readonly(*ask_mint, false),
+    AccountMeta::new(keys.treasury(ask_mint), false),
+  ];
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  let mut values = vec![amount, limit];
+  if let Some((referrers, referrer, referral_ratio)) = referral {
+    accounts.push(AccountMeta::new_readonly(*referrers, false));
+    accounts.push(AccountMeta::new(
+      get_associated_token_address(referrer, ask_mint),
+      false,
+    ));
+    values.push(referral_ratio);
+  }
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: data(3, &values),
+  }
+}
+
+///
+/// An instruction of an authority over the pool (plus an optional target account)
+///
+fn authority(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  pool: &Pubkey,
+  target: Option<&Pubkey>,
+  signers: &[Pubkey],
+  data: Vec<u8>,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new_readonly(*authority, signers.is_empty()),
+    AccountMeta::new(*pool, false),
+  ];
+  if let Some(target) = target {
+    accounts.push(AccountMeta::new_readonly(*target, false));
+  }
+  accounts.append(&mut signer_accounts(signers));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+pub fn freeze_pool(program_id: &Pubkey, pauser: &Pubkey, pool: &Pubkey, mask: u8) -> Instruction {
+  authority(program_id, pauser, pool, None, &[], vec![4, mask])
+}
+
+pub fn thaw_pool(program_id: &Pubkey, owner: &Pubkey, pool: &Pubkey, mask: u8) -> Instruction {
+  authority(program_id, owner, pool, None, &[], vec![5, mask])
+}
+
+pub fn transfer_taxman(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  pool: &Pubkey,
+  taxman: &Pubkey,
+) -> Instruction {
+  authority(program_id, owner, pool, Some(taxman), &[], vec![6])
+}
+
+pub fn transfer_ownership(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  pool: &Pubkey,
+  new_owner: &Pubkey,
+) -> Instruction {
+  authority(program_id, owner, pool, Some(new_owner), &[], vec![7])
+}
+
+pub fn transfer_pauser(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  pool: &Pubkey,
+  pauser: &Pubkey,
+) -> Instruction {
+  authority(program_id, owner, pool, Some(pauser), &[], vec![11])
+}
+
+pub fn transfer_fee_manager(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  pool: &Pubkey,
+  fee_manager: &Pubkey,
+) -> Instruction {
+  authority(program_id, owner, pool, Some(fee_manager), &[], vec![12])
+}
+
+///
+/// Transfer ownership to a multisig, approved by its signers
+///
+pub fn transfer_ownership_multisig(
+  program_id: &Pubkey,
+  multisig: &Pubkey,
+  pool: &Pubkey,
+  new_owner: &Pubkey,
+  signers: &[Pubkey],
+) -> Instruction {
+  authority(
+    program_id,
+    multisig,
+    pool,
+    Some(new_owner),
+    signers,
+    vec![7],
+  )
+}
+
+pub fn update_fee(
+  program_id: &Pubkey,
+  fee_manager: &Pubkey,
+  pool: &Pubkey,
+  fee_ratio: u64,
+  tax_ratio: u64,
+  max_referral_ratio: Option<u64>,
+) -> Instruction {
+  let mut values = vec![fee_ratio, tax_ratio];
+  values.extend(max_referral_ratio);
+  authority(program_id, fee_manager, pool, None, &[], data(9, &values))
+}
+
+pub fn set_fee_tiers(
+  program_id: &Pubkey,
+  fee_manager: &Pubkey,
+  pool: &Pubkey,
+  fee_tiers: &Pubkey,
+) -> Instruction {
+  authority(
+    program_id,
+    fee_manager,
+    pool,
+    Some(fee_tiers),
+    &[],
+    vec![28],
+  )
+}
+
+pub fn set_allowlist(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  pool: &Pubkey,
+  allowlist: &Pubkey,
+) -> Instruction {
+  authority(program_id, owner, pool, Some(allowlist), &[], vec![31])
+}
+
+pub fn set_referrers(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  pool: &Pubkey,
+  referrers: &Pubkey,
+) -> Instruction {
+  authority(program_id, owner, pool, Some(referrers), &[], vec![35])
+}
+
+pub fn initialize_multisig(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  multisig: &Pubkey,
+  m: u8,
+  signers: &[Pubkey],
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new(*multisig, true),
+    AccountMeta::new_readonly(system_program::id(), false),
+    AccountMeta::new_readonly(sysvar::rent::id(), false),
+  ];
+  accounts.extend(
+    signers
+      .iter()
+      .map(|signer| AccountMeta::new_readonly(*signer, false)),
+  );
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: vec![13, m],
+  }
+}
+
+///
+/// Sync (15) and GetDrift (17) take the same accounts
+///
+pub fn sync(program_id: &Pubkey, keys: &PoolKeys) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(keys.pool, false),
+      AccountMeta::new_readonly(keys.treasury_a, false),
+      AccountMeta::new_readonly(keys.treasury_b, false),
+    ],
+    data: vec![15],
+  }
+}
+
+pub fn get_drift(program_id: &Pubkey, keys: &PoolKeys) -> Instruction {
+  Instruction {
+    data: vec![17],
+    ..sync(program_id, keys)
+  }
+}
+
+pub fn skim(program_id: &Pubkey, payer: &Pubkey, keys: &PoolKeys, taxman: &Pubkey) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new(keys.pool, false),
+    AccountMeta::new_readonly(keys.mint_a, false),
+    AccountMeta::new(keys.treasury_a, false),
+    AccountMeta::new_readonly(keys.mint_b, false),
+    AccountMeta::new(keys.treasury_b, false),
+    AccountMeta::new_readonly(*taxman, false),
+    AccountMeta::new(get_associated_token_address(taxman, &keys.mint_a), false),
+    AccountMeta::new(get_associated_token_address(taxman, &keys.mint_b), false),
+  ];
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: vec![16],
+  }
+}
+
+pub fn close_pool(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  recipient: &Pubkey,
+  signers: &[Pubkey],
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*owner, signers.is_empty()),
+    AccountMeta::new(keys.pool, false),
+    AccountMeta::new_readonly(keys.mint_lpt, false),
+  ];
+  accounts.append(&mut pair_accounts(recipient, keys));
+  accounts.push(AccountMeta::new(*recipient, false));
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  accounts.append(&mut signer_accounts(signers));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: vec![18],
+  }
+}
+
+///
+/// Borrow from the pool and repay (amount + fee) of each side through the borrower
+///
+pub fn flash_loan(
+  program_id: &Pubkey,
+  borrower_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  amounts: (u64, u64),
+  repayments: (u64, u64),
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*owner, true),
+    AccountMeta::new(keys.pool, false),
+  ];
+  accounts.append(&mut pair_accounts(owner, keys));
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  accounts.append(&mut borrower_accounts(
+    borrower_id,
+    owner,
+    keys,
+    &[keys.mint_a, keys.mint_b],
+  ));
+  let mut data = data(20, &[amounts.0, amounts.1]);
+  data.extend_from_slice(&repayments.0.to_le_bytes());
+  data.extend_from_slice(&repayments.1.to_le_bytes());
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+///
+/// Receive the ask first, then pay the bid through the borrower
+///
+#[allow(clippy::too_many_arguments)]
+pub fn flash_swap(
+  program_id: &Pubkey,
+  borrower_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  bid_mint: &Pubkey,
+  ask_mint: &Pubkey,
+  amount: u64,
+  limit: u64,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*owner, true),
+    AccountMeta::new(keys.pool, false),
+    AccountMeta::new_readonly(*bid_mint, false),
+    AccountMeta::new(keys.treasury(bid_mint), false),
+    AccountMeta::new(get_associated_token_address(owner, ask_mint), false),
+    AccountMeta::new_readonly(*ask_mint, false),
+    AccountMeta::new(keys.treasury(ask_mint), false),
+  ];
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  accounts.append(&mut borrower_accounts(
+    borrower_id,
+    owner,
+    keys,
+    &[*bid_mint],
+  ));
+  let mut data = data(21, &[amount, limit]);
+  data.extend_from_slice(&amount.to_le_bytes());
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+///
+/// The borrower program, then its own accounts (see repay)
+///
+fn borrower_accounts(
+  borrower_id: &Pubkey,
+  owner: &Pubkey,
+  keys: &PoolKeys,
+  mints: &[Pubkey],
+) -> Vec<AccountMeta> {
+  let mut accounts = vec![
+    AccountMeta::new_readonly(*borrower_id, false),
+    AccountMeta::new_readonly(spl_token::id(), false),
+    AccountMeta::new_readonly(*owner, true),
+  ];
+  for mint in mints.iter() {
+    accounts.push(AccountMeta::new(
+      get_associated_token_address(owner, mint),
+      false,
+    ));
+    accounts.push(AccountMeta::new(keys.treasury(mint), false));
+  }
+  accounts
+}
+
+///
+/// ClaimTax to the taxman's associated accounts, funded by the payer
+///
+pub fn claim_tax(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  taxman: &Pubkey,
+  keys: &PoolKeys,
+  signers: &[Pubkey],
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new_readonly(*taxman, signers.is_empty()),
+    AccountMeta::new(keys.pool, false),
+  ];
+  accounts.append(&mut pair_accounts(taxman, keys));
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  accounts.append(&mut signer_accounts(signers));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: vec![22],
+  }
+}
+
+///
+/// Initialize a program account (distribution, fee tiers, allowlist, referrers) under an authority
+///
+fn initialize_config(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  config: &Pubkey,
+  authority: &Pubkey,
+  extra: Option<&Pubkey>,
+  data: Vec<u8>,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new(*config, true),
+    AccountMeta::new_readonly(*authority, false),
+  ];
+  if let Some(extra) = extra {
+    accounts.push(AccountMeta::new_readonly(*extra, false));
+  }
+  accounts.push(AccountMeta::new_readonly(system_program::id(), false));
+  accounts.push(AccountMeta::new_readonly(sysvar::rent::id(), false));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+///
+/// Update a program account as its authority (the listed keys come first)
+///
+fn update_config(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  config: &Pubkey,
+  keys: &[Pubkey],
+  data: Vec<u8>,
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new_readonly(*authority, true),
+    AccountMeta::new(*config, false),
+  ];
+  accounts.extend(
+    keys
+      .iter()
+      .map(|key| AccountMeta::new_readonly(*key, false)),
+  );
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+fn shares_data(tag: u8, shares: &[u16]) -> Vec<u8> {
+  let mut data = vec![tag];
+  for share in shares.iter() {
+    data.extend_from_slice(&share.to_le_bytes());
+  }
+  data
+}
+
+fn tiers_data(tag: u8, tiers: &[(u64, u64)]) -> Vec<u8> {
+  let values: Vec<u64> = tiers
+    .iter()
+    .flat_map(|&(threshold, discount)| vec![threshold, discount])
+    .collect();
+  data(tag, &values)
+}
+
+pub fn initialize_distribution(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  distribution: &Pubkey,
+  authority: &Pubkey,
+  recipients: &[Pubkey],
+  shares: &[u16],
+) -> Instruction {
+  let mut ix = initialize_config(
+    program_id,
+    payer,
+    distribution,
+    authority,
+    None,
+    shares_data(23, shares),
+  );
+  ix.accounts.extend(
+    recipients
+      .iter()
+      .map(|key| AccountMeta::new_readonly(*key, false)),
+  );
+  ix
+}
+
+pub fn update_distribution(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  distribution: &Pubkey,
+  recipients: &[Pubkey],
+  shares: &[u16],
+) -> Instruction {
+  update_config(
+    program_id,
+    authority,
+    distribution,
+    recipients,
+    shares_data(24, shares),
+  )
+}
+
+///
+/// DistributeTax to the recipients' associated accounts
+///
+pub fn distribute_tax(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  keys: &PoolKeys,
+  distribution: &Pubkey,
+  recipients: &[Pubkey],
+) -> Instruction {
+  let mut accounts = vec![
+    AccountMeta::new(*payer, true),
+    AccountMeta::new(keys.pool, false),
+    AccountMeta::new_readonly(keys.mint_a, false),
+    AccountMeta::new(keys.treasury_a, false),
+    AccountMeta::new_readonly(keys.mint_b, false),
+    AccountMeta::new(keys.treasury_b, false),
+    AccountMeta::new_readonly(*distribution, false),
+  ];
+  accounts.append(&mut program_accounts(&keys.treasurer));
+  for recipient in recipients.iter() {
+    accounts.push(AccountMeta::new_readonly(*recipient, false));
+    accounts.push(AccountMeta::new(
+      get_associated_token_address(recipient, &keys.mint_a),
+      false,
+    ));
+    accounts.push(AccountMeta::new(
+      get_associated_token_address(recipient, &keys.mint_b),
+      false,
+    ));
+  }
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data: vec![25],
+  }
+}
+
+pub fn initialize_fee_tiers(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  fee_tiers: &Pubkey,
+  authority: &Pubkey,
+  mint: &Pubkey,
+  tiers: &[(u64, u64)],
+) -> Instruction {
+  initialize_config(
+    program_id,
+    payer,
+    fee_tiers,
+    authority,
+    Some(mint),
+    tiers_data(26, tiers),
+  )
+}
+
+pub fn update_fee_tiers(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  fee_tiers: &Pubkey,
+  tiers: &[(u64, u64)],
+) -> Instruction {
+  update_config(program_id, authority, fee_tiers, &[], tiers_data(27, tiers))
+}
+
+pub fn initialize_allowlist(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  allowlist: &Pubkey,
+  authority: &Pubkey,
+  discount: u64,
+) -> Instruction {
+  initialize_config(
+    program_id,
+    payer,
+    allowlist,
+    authority,
+    None,
+    data(29, &[discount]),
+  )
+}
+
+pub fn update_allowlist(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  allowlist: &Pubkey,
+  discount: u64,
+  members: &[Pubkey],
+) -> Instruction {
+  let mut data = data(30, &[discount]);
+  data.push(members.len() as u8);
+  update_config(program_id, authority, allowlist, members, data)
+}
+
+pub fn initialize_referrers(
+  program_id: &Pubkey,
+  payer: &Pubkey,
+  referrers: &Pubkey,
+  authority: &Pubkey,
+  members: &[Pubkey],
+) -> Instruction {
+  let mut ix = initialize_config(program_id, payer, referrers, authority, None, vec![33]);
+  ix.accounts.extend(
+    members
+      .iter()
+      .map(|member| AccountMeta::new_readonly(*member, false)),
+  );
+  ix
+}
+
+pub fn update_referrers(
+  program_id: &Pubkey,
+  authority: &Pubkey,
+  referrers: &Pubkey,
+  members: &[Pubkey],
+) -> Instruction {
+  update_config(
+    program_id,
+    authority,
+    referrers,
+    members,
+    vec![34, members.len() as u8],
+  )
+}
+
+///
+/// Stake from (or unstake to) the owner's associated account of the mint
+///
+fn stake_accounts(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  stake: &Pubkey,
+  mint: &Pubkey,
+  is_signer: bool,
+  data: Vec<u8>,
+) -> Instruction {
+  let treasurer = Pubkey::create_program_address(&[&stake.to_bytes()], program_id).unwrap();
+  let mut accounts = vec![
+    AccountMeta::new(*owner, true),
+    AccountMeta::new(*stake, is_signer),
+    AccountMeta::new(get_associated_token_address(owner, mint), false),
+    AccountMeta::new_readonly(*mint, false),
+    AccountMeta::new(get_associated_token_address(&treasurer, mint), false),
+  ];
+  accounts.append(&mut program_accounts(&treasurer));
+  Instruction {
+    program_id: *program_id,
+    accounts,
+    data,
+  }
+}
+
+///
+/// The stake account signs so that the first deposit can create it
+///
+pub fn stake(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  stake: &Pubkey,
+  mint: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  stake_accounts(program_id, owner, stake, mint, true, data(37, &[amount]))
+}
+
+pub fn unstake(
+  program_id: &Pubkey,
+  owner: &Pubkey,
+  stake: &Pubkey,
+  mint: &Pubkey,
+  amount: u64,
+) -> Instruction {
+  stake_accounts(program_id, owner, stake, mint, false, data(38, &[amount]))
+}
+
+///
+/// Send the instruction through the partner program
+///
+pub fn through_partner(partner_id: &Pubkey, ix: Instruction) -> Instruction {
+  let mut accounts = vec![AccountMeta::new_readonly(ix.program_id, false)];
+  accounts.extend(ix.accounts);
+  Instruction {
+    program_id: *partner_id,
+    accounts,
+    data: ix.data,
+  }
+}
+
+pub fn migrate_pool(program_id: &Pubkey, payer: &Pubkey, pool: &Pubkey) -> Instruction {
+  Instruction {
+    program_id: *program_id,
+    accounts: vec![
+      AccountMeta::new(*payer, true),
+      AccountMeta::new(*pool, false),
+      AccountMeta::new_readonly(system_program::id(), false),
+      AccountMeta::new_readonly(sysvar::rent::id(), false),
+    ],
+    data: vec![32],
+  }
+}
@@ -0,0 +1,595 @@
+//! Compute unit benchmarks of every instruction. The numbers are only meaningful
+//! against the BPF build, so they are not part of `cargo test`. Run them by hand
+//! with `npm run bench`, which is
+//! `cargo test-sbf --test compute_units -- --ignored --nocapture`,
+//! before merging a change to the program.
+mod common;
+
+use common::*;
+use main::{
+  router::Router,
+  schema::{
+    pool::{Pool, LEGACY_POOL_LEN, MAX_REFERRAL_RATIO},
+    pool_state::{PoolState, PAUSE_SWAP},
+    pool_trait::Exchange,
+    stake::HOLDING_SLOTS,
+  },
+};
+use solana_program_test::tokio;
+use solana_sdk::{
+  account::Account,
+  instruction::{AccountMeta, Instruction},
+  pubkey::Pubkey,
+  rent::Rent,
+  signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+///
+/// Compute unit ceilings. Going over one is a regression, tighten them as the program improves.
+///
+const BUDGETS: &[(&str, u64)] = &[
+  ("InitializePool", 150_000),
+  ("AddLiquidity (fresh LP account)", 70_000),
+  ("AddLiquidity (existing accounts)", 40_000),
+  ("AddSidedLiquidity (balanced)", 60_000),
+  ("AddSidedLiquidity (single side)", 120_000),
+  ("AddSidedLiquidity (large imbalance)", 120_000),
+  ("RemoveLiquidity (fresh accounts)", 100_000),
+  ("RemoveLiquidity (existing accounts)", 40_000),
+  ("Swap (fresh ask account)", 70_000),
+  ("Swap (existing accounts)", 35_000),
+  ("Swap (referral)", 45_000),
+  ("Swap (fee tier)", 45_000),
+  ("Swap (allowlisted)", 45_000),
+  ("Route (1 hop)", 45_000),
+  ("Route (2 hops)", 90_000),
+  ("Route (3 hops)", 135_000),
+  ("Route (4 hops)", 180_000),
+  ("Route (2 hops, fresh accounts)", 150_000),
+  ("SplitRoute (2 paths)", 140_000),
+  ("FlashLoan", 80_000),
+  ("FlashSwap", 60_000),
+  ("FreezePool", 15_000),
+  ("ThawPool", 15_000),
+  ("TransferTaxman", 15_000),
+  ("TransferOwnership", 15_000),
+  ("TransferPauser", 15_000),
+  ("TransferFeeManager", 15_000),
+  ("UpdateFee", 15_000),
+  ("InitializeMultisig", 25_000),
+  ("EmergencyWithdraw", 50_000),
+  ("GetDrift", 15_000),
+  ("Skim", 50_000),
+  ("Sync", 15_000),
+  ("ClaimTax", 50_000),
+  ("InitializeDistribution", 25_000),
+  ("UpdateDistribution", 15_000),
+  ("DistributeTax (2 fresh recipients)", 200_000),
+  ("InitializeFeeTiers", 25_000),
+  ("UpdateFeeTiers", 15_000),
+  ("SetFeeTiers", 15_000),
+  ("InitializeAllowlist", 25_000),
+  ("UpdateAllowlist", 15_000),
+  ("SetAllowlist", 15_000),
+  ("InitializeReferrers", 25_000),
+  ("UpdateReferrers", 15_000),
+  ("SetReferrers", 15_000),
+  ("Stake (fresh account)", 70_000),
+  ("Unstake", 40_000),
+  ("MigratePool", 25_000),
+  ("ClosePool", 100_000),
+];
+
+const FEE_RATIO: u64 = 2_500_000; // 0.25%
+const TAX_RATIO: u64 = 500_000; // 0.05%
+const RESERVE: u64 = 1_000_000_000_000;
+
+struct Bench {
+  env: Env,
+  results: Vec<(&'static str, u64)>,
+}
+
+impl Bench {
+  async fn measure(&mut self, name: &'static str, ixs: &[Instruction], signers: &[&Keypair]) {
+    let units = self
+      .env
+      .process_with_units(ixs, signers)
+      .await
+      .unwrap_or_else(|error| panic!("{} failed: {:?}", name, error));
+    self.results.push((name, units));
+  }
+
+  ///
+  /// Print the report and check every scenario against its budget
+  ///
+  fn report(&self) {
+    println!("{:<40} {:>10} {:>10}", "Instruction", "Units", "Budget");
+    let mut regressions = vec![];
+    for &(name, budget) in BUDGETS.iter() {
+      let units = self
+        .results
+        .iter()
+        .find(|(measured, _)| *measured == name)
+        .map(|&(_, units)| units)
+        .unwrap_or_else(|| panic!("{} was not measured", name));
+      println!("{:<40} {:>10} {:>10}", name, units, budget);
+      if units > budget {
+        regressions.push(name);
+      }
+    }
+    assert!(regressions.is_empty(), "over budget: {:?}", regressions);
+  }
+}
+
+///
+/// A pool in the original 257-byte layout
+///
+fn legacy_pool(program_id: &Pubkey) -> Account {
+  let mut data = vec![];
+  data.extend_from_slice(&Pubkey::new_unique().to_bytes()); // owner
+  data.push(PoolState::Initialized as u8);
+  for _ in 0..4 {
+    data.extend_from_slice(&Pubkey::new_unique().to_bytes()); // mint_lpt, taxman, mint_a, treasury_a
+  }
+  data.extend_from_slice(&RESERVE.to_le_bytes());
+  for _ in 0..2 {
+    data.extend_from_slice(&Pubkey::new_unique().to_bytes()); // mint_b, treasury_b
+  }
+  data.extend_from_slice(&RESERVE.to_le_bytes());
+  data.extend_from_slice(&FEE_RATIO.to_le_bytes());
+  data.extend_from_slice(&TAX_RATIO.to_le_bytes());
+  assert_eq!(data.len(), LEGACY_POOL_LEN);
+  Account {
+    lamports: Rent::default().minimum_balance(LEGACY_POOL_LEN),
+    data,
+    owner: *program_id,
+    executable: false,
+    rent_epoch: 0,
+  }
+}
+
+#[tokio::test]
+#[ignore = "requires cargo test-sbf"]
+async fn compute_units() {
+  assert!(
+    std::env::var("BPF_OUT_DIR").is_ok() || std::env::var("SBF_OUT_DIR").is_ok(),
+    "compute units are only metered on the BPF build"
+  );
+  let legacy = Pubkey::new_unique();
+  let env = Env::start_with(|program_test, program_id| {
+    program_test.add_account(legacy, legacy_pool(program_id));
+  })
+  .await;
+  let mut bench = Bench {
+    env,
+    results: vec![],
+  };
+  let program_id = bench.env.program_id;
+  let borrower_id = bench.env.borrower_id;
+  let payer = bench.env.payer();
+
+  // Five mints chained by four pools, plus a direct A-C pool for split routes
+  let mut mints = vec![];
+  for _ in 0..5 {
+    mints.push(bench.env.create_mint().await);
+  }
+  let (a, b, c) = (mints[0], mints[1], mints[2]);
+  bench.env.mint_to(&payer, &a, RESERVE).await;
+  bench.env.mint_to(&payer, &b, RESERVE).await;
+  let pool = pool_keypair(&program_id);
+  let mint_lpt = Keypair::new();
+  let ab = PoolKeys::new(&program_id, pool.pubkey(), mint_lpt.pubkey(), a, b);
+  let ix = initialize_pool(
+    &program_id,
+    &payer,
+    &payer,
+    &payer,
+    &ab,
+    RESERVE,
+    RESERVE,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  bench
+    .measure("InitializePool", &[ix], &[&pool, &mint_lpt])
+    .await;
+  let mut chain = vec![ab];
+  for pair in mints[1..].windows(2) {
+    let keys = bench
+      .env
+      .create_pool(&pair[0], &pair[1], RESERVE, RESERVE, FEE_RATIO, TAX_RATIO)
+      .await;
+    chain.push(keys);
+  }
+  let ac = bench
+    .env
+    .create_pool(&a, &c, RESERVE, RESERVE, FEE_RATIO, TAX_RATIO)
+    .await;
+
+  // Liquidity
+  let lp = bench.env.create_wallet().await;
+  bench.env.mint_to(&lp.pubkey(), &a, RESERVE).await;
+  bench.env.mint_to(&lp.pubkey(), &b, RESERVE).await;
+  let ix = add_liquidity(&program_id, &lp.pubkey(), &ab, 1_000_000, 1_000_000);
+  bench
+    .measure("AddLiquidity (fresh LP account)", &[ix], &[&lp])
+    .await;
+  let ix = add_liquidity(&program_id, &lp.pubkey(), &ab, 2_000_000, 2_000_000);
+  bench
+    .measure("AddLiquidity (existing accounts)", &[ix], &[&lp])
+    .await;
+  let ix = add_sided_liquidity(&program_id, &lp.pubkey(), &ab, 1_000_000, 1_000_000);
+  bench
+    .measure("AddSidedLiquidity (balanced)", &[ix], &[&lp])
+    .await;
+  let ix = add_sided_liquidity(&program_id, &lp.pubkey(), &ab, 1_000_000, 0);
+  bench
+    .measure("AddSidedLiquidity (single side)", &[ix], &[&lp])
+    .await;
+  let ix = add_sided_liquidity(&program_id, &lp.pubkey(), &ab, 10_000_000_000, 1_000);
+  bench
+    .measure("AddSidedLiquidity (large imbalance)", &[ix], &[&lp])
+    .await;
+  let lpt = bench.env.balance(&lp.pubkey(), &ab.mint_lpt).await;
+  let ix = remove_liquidity(&program_id, &lp.pubkey(), &ab, lpt / 4);
+  bench
+    .measure("RemoveLiquidity (existing accounts)", &[ix], &[&lp])
+    .await;
+  // A holder of LP tokens only
+  let holder = bench.env.create_wallet().await;
+  let holder_lpt = bench.env.create_ata(&holder.pubkey(), &ab.mint_lpt).await;
+  let ix = spl_token::instruction::transfer(
+    &spl_token::id(),
+    &get_associated_token_address(&lp.pubkey(), &ab.mint_lpt),
+    &holder_lpt,
+    &lp.pubkey(),
+    &[],
+    lpt / 4,
+  )
+  .unwrap();
+  bench.env.process(&[ix], &[&lp]).await.unwrap();
+  let ix = remove_liquidity(&program_id, &holder.pubkey(), &ab, lpt / 4);
+  bench
+    .measure("RemoveLiquidity (fresh accounts)", &[ix], &[&holder])
+    .await;
+
+  // Swaps
+  let trader = bench.env.create_wallet().await;
+  bench.env.mint_to(&trader.pubkey(), &a, RESERVE).await;
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &ab,
+    &a,
+    &b,
+    1_000_000,
+    0,
+    None,
+  );
+  bench
+    .measure("Swap (fresh ask account)", &[ix], &[&trader])
+    .await;
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &ab,
+    &a,
+    &b,
+    2_000_000,
+    0,
+    None,
+  );
+  bench
+    .measure("Swap (existing accounts)", &[ix], &[&trader])
+    .await;
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &ab.pool,
+    FEE_RATIO,
+    TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO),
+  );
+  bench.measure("UpdateFee", &[ix], &[]).await;
+  let referrer = Pubkey::new_unique();
+  bench.env.create_ata(&referrer, &b).await;
+  let referrers = Keypair::new();
+  let ix = initialize_referrers(
+    &program_id,
+    &payer,
+    &referrers.pubkey(),
+    &payer,
+    &[referrer],
+  );
+  bench
+    .measure("InitializeReferrers", &[ix], &[&referrers])
+    .await;
+  let ix = update_referrers(&program_id, &payer, &referrers.pubkey(), &[referrer]);
+  bench.measure("UpdateReferrers", &[ix], &[]).await;
+  let ix = set_referrers(&program_id, &payer, &ab.pool, &referrers.pubkey());
+  bench.measure("SetReferrers", &[ix], &[]).await;
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &ab,
+    &a,
+    &b,
+    3_000_000,
+    0,
+    Some((&referrers.pubkey(), &referrer, MAX_REFERRAL_RATIO)),
+  );
+  bench.measure("Swap (referral)", &[ix], &[&trader]).await;
+
+  // Routes are paid by the payer to fit 4 hops in a transaction
+  bench.env.mint_to(&payer, &a, RESERVE).await;
+  let mut pools = vec![];
+  for keys in chain.iter().chain([ac].iter()) {
+    pools.push((keys.pool, bench.env.pool(&keys.pool).await));
+  }
+  let router = Router::new(&pools);
+  let legs: Vec<(Pubkey, Pubkey, Pubkey)> = chain
+    .iter()
+    .map(|keys| (keys.pool, keys.mint_a, keys.mint_b))
+    .collect();
+  for (hops, name) in [
+    (1, "Route (1 hop)"),
+    (2, "Route (2 hops)"),
+    (3, "Route (3 hops)"),
+    (4, "Route (4 hops)"),
+  ]
+  .iter()
+  {
+    let path = router.quote(&legs[..*hops], 1_000_000).unwrap();
+    let ix = router
+      .route_instruction(&program_id, &payer, &path, 0)
+      .unwrap();
+    bench.measure(name, &[ix], &[]).await;
+  }
+  let newcomer = bench.env.create_wallet().await;
+  bench.env.mint_to(&newcomer.pubkey(), &a, RESERVE).await;
+  let path = router.quote(&legs[..2], 1_000_000).unwrap();
+  let ix = router
+    .route_instruction(&program_id, &newcomer.pubkey(), &path, 0)
+    .unwrap();
+  bench
+    .measure("Route (2 hops, fresh accounts)", &[ix], &[&newcomer])
+    .await;
+  let direct = router.quote(&[(ac.pool, a, c)], 1_000_000).unwrap();
+  let detour = router.quote(&legs[..2], 1_000_000).unwrap();
+  let ix = router
+    .split_route_instruction(&program_id, &payer, &[direct, detour], 0)
+    .unwrap();
+  bench.measure("SplitRoute (2 paths)", &[ix], &[]).await;
+
+  // Flash loans, repaid by the trader's own funds
+  bench.env.mint_to(&trader.pubkey(), &b, RESERVE).await;
+  let pool_data: Pool = bench.env.pool(&ab.pool).await;
+  let (amount_a, amount_b) = (10_000_000, 20_000_000);
+  let repayments = (
+    amount_a + pool_data.flash_fee(amount_a).unwrap(),
+    amount_b + pool_data.flash_fee(amount_b).unwrap(),
+  );
+  let ix = flash_loan(
+    &program_id,
+    &borrower_id,
+    &trader.pubkey(),
+    &ab,
+    (amount_a, amount_b),
+    repayments,
+  );
+  bench.measure("FlashLoan", &[ix], &[&trader]).await;
+  let ix = flash_swap(
+    &program_id,
+    &borrower_id,
+    &trader.pubkey(),
+    &ab,
+    &a,
+    &b,
+    1_000_000,
+    0,
+  );
+  bench.measure("FlashSwap", &[ix], &[&trader]).await;
+
+  // Roles
+  let ix = freeze_pool(&program_id, &payer, &ab.pool, PAUSE_SWAP);
+  bench.measure("FreezePool", &[ix], &[]).await;
+  let ix = thaw_pool(&program_id, &payer, &ab.pool, PAUSE_SWAP);
+  bench.measure("ThawPool", &[ix], &[]).await;
+  let ix = transfer_taxman(&program_id, &payer, &ab.pool, &payer);
+  bench.measure("TransferTaxman", &[ix], &[]).await;
+  let ix = transfer_pauser(&program_id, &payer, &ab.pool, &payer);
+  bench.measure("TransferPauser", &[ix], &[]).await;
+  let ix = transfer_fee_manager(&program_id, &payer, &ab.pool, &payer);
+  bench.measure("TransferFeeManager", &[ix], &[]).await;
+  let ix = transfer_ownership(&program_id, &payer, &ab.pool, &payer);
+  bench.measure("TransferOwnership", &[ix], &[]).await;
+  let multisig = Keypair::new();
+  let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+  let ix = initialize_multisig(&program_id, &payer, &multisig.pubkey(), 2, &members);
+  bench
+    .measure("InitializeMultisig", &[ix], &[&multisig])
+    .await;
+  let ix = emergency_withdraw(&program_id, &lp.pubkey(), &ab, 1_000);
+  bench.measure("EmergencyWithdraw", &[ix], &[&lp]).await;
+
+  // Drift, after a donation to treasury A
+  let ix = spl_token::instruction::transfer(
+    &spl_token::id(),
+    &get_associated_token_address(&trader.pubkey(), &a),
+    &ab.treasury_a,
+    &trader.pubkey(),
+    &[],
+    1_000_000,
+  )
+  .unwrap();
+  bench.env.process(&[ix], &[&trader]).await.unwrap();
+  let ix = get_drift(&program_id, &ab);
+  bench.measure("GetDrift", &[ix], &[]).await;
+  let ix = skim(&program_id, &payer, &ab, &payer);
+  bench.measure("Skim", &[ix], &[]).await;
+  let ix = sync(&program_id, &ab);
+  bench.measure("Sync", &[ix], &[]).await;
+  let ix = claim_tax(&program_id, &payer, &payer, &ab, &[]);
+  bench.measure("ClaimTax", &[ix], &[]).await;
+
+  // Tax distribution
+  let distribution = Keypair::new();
+  let recipients = [Pubkey::new_unique(), Pubkey::new_unique()];
+  let ix = initialize_distribution(
+    &program_id,
+    &payer,
+    &distribution.pubkey(),
+    &payer,
+    &recipients,
+    &[5000, 5000],
+  );
+  bench
+    .measure("InitializeDistribution", &[ix], &[&distribution])
+    .await;
+  let ix = update_distribution(
+    &program_id,
+    &payer,
+    &distribution.pubkey(),
+    &recipients,
+    &[4000, 6000],
+  );
+  bench.measure("UpdateDistribution", &[ix], &[]).await;
+  let ix = transfer_taxman(&program_id, &payer, &ab.pool, &distribution.pubkey());
+  bench.env.process(&[ix], &[]).await.unwrap();
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &ab,
+    &b,
+    &a,
+    5_000_000,
+    0,
+    None,
+  );
+  bench.env.process(&[ix], &[&trader]).await.unwrap();
+  let ix = distribute_tax(
+    &program_id,
+    &payer,
+    &ab,
+    &distribution.pubkey(),
+    &recipients,
+  );
+  bench
+    .measure("DistributeTax (2 fresh recipients)", &[ix], &[])
+    .await;
+
+  // Fee tiers over a governance mint staked by the trader
+  let governance = mints[4];
+  bench
+    .env
+    .mint_to(&trader.pubkey(), &governance, 1_000_000)
+    .await;
+  let stake_acc = pool_keypair(&program_id);
+  let ix = stake(
+    &program_id,
+    &trader.pubkey(),
+    &stake_acc.pubkey(),
+    &governance,
+    1_000_000,
+  );
+  bench
+    .measure("Stake (fresh account)", &[ix], &[&trader, &stake_acc])
+    .await;
+  bench.env.warp(HOLDING_SLOTS).await;
+  let fee_tiers = Keypair::new();
+  let ix = initialize_fee_tiers(
+    &program_id,
+    &payer,
+    &fee_tiers.pubkey(),
+    &payer,
+    &governance,
+    &[(1_000, 100_000_000)],
+  );
+  bench
+    .measure("InitializeFeeTiers", &[ix], &[&fee_tiers])
+    .await;
+  let ix = update_fee_tiers(
+    &program_id,
+    &payer,
+    &fee_tiers.pubkey(),
+    &[(1_000, 100_000_000), (100_000, 200_000_000)],
+  );
+  bench.measure("UpdateFeeTiers", &[ix], &[]).await;
+  let ix = set_fee_tiers(&program_id, &payer, &ab.pool, &fee_tiers.pubkey());
+  bench.measure("SetFeeTiers", &[ix], &[]).await;
+  let mut ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &ab,
+    &a,
+    &b,
+    4_000_000,
+    0,
+    None,
+  );
+  ix.accounts
+    .push(AccountMeta::new_readonly(fee_tiers.pubkey(), false));
+  ix.accounts
+    .push(AccountMeta::new_readonly(stake_acc.pubkey(), false));
+  bench.measure("Swap (fee tier)", &[ix], &[&trader]).await;
+  let ix = unstake(
+    &program_id,
+    &trader.pubkey(),
+    &stake_acc.pubkey(),
+    &governance,
+    1_000_000,
+  );
+  bench.measure("Unstake", &[ix], &[&trader]).await;
+
+  // Allowlist with the trader as the only member
+  let allowlist = Keypair::new();
+  let ix = initialize_allowlist(
+    &program_id,
+    &payer,
+    &allowlist.pubkey(),
+    &payer,
+    100_000_000,
+  );
+  bench
+    .measure("InitializeAllowlist", &[ix], &[&allowlist])
+    .await;
+  let ix = update_allowlist(
+    &program_id,
+    &payer,
+    &allowlist.pubkey(),
+    200_000_000,
+    &[trader.pubkey()],
+  );
+  bench.measure("UpdateAllowlist", &[ix], &[]).await;
+  let ix = set_allowlist(&program_id, &payer, &ab.pool, &allowlist.pubkey());
+  bench.measure("SetAllowlist", &[ix], &[]).await;
+  let mut ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &ab,
+    &a,
+    &b,
+    5_000_000,
+    0,
+    None,
+  );
+  ix.accounts
+    .push(AccountMeta::new_readonly(allowlist.pubkey(), false));
+  bench.measure("Swap (allowlisted)", &[ix], &[&trader]).await;
+
+  let ix = migrate_pool(&program_id, &payer, &legacy);
+  bench.measure("MigratePool", &[ix], &[]).await;
+
+  // Close a pool after its whole liquidity is withdrawn
+  let last = chain[3];
+  let lpt = bench.env.balance(&payer, &last.mint_lpt).await;
+  let ix = remove_liquidity(&program_id, &payer, &last, lpt);
+  bench.env.process(&[ix], &[]).await.unwrap();
+  let ix = claim_tax(&program_id, &payer, &payer, &last, &[]);
+  bench.env.process(&[ix], &[]).await.unwrap();
+  let ix = close_pool(&program_id, &payer, &payer, &last, &payer, &[]);
+  bench.measure("ClosePool", &[ix], &[]).await;
+
+  bench.report();
+}
@@ -0,0 +1,169 @@
+use main::{
+  processor::add_sided_liquidity::{rake, split},
+  schema::{
+    pool::{extract, Pool, MAX_FEE_RATIO, MAX_TAX_RATIO},
+    pool_state::PoolState,
+    pool_trait::Exchange,
+  },
+};
+use proptest::prelude::*;
+use solana_program::{account_info::AccountInfo, program_pack::Pack, pubkey::Pubkey};
+
+fn pool(reserve_a: u64, reserve_b: u64, fee_ratio: u64, tax_ratio: u64) -> Pool {
+  Pool {
+    state: PoolState::Initialized,
+    mint_a: Pubkey::new_from_array([1; 32]),
+    reserve_a,
+    mint_b: Pubkey::new_from_array([2; 32]),
+    reserve_b,
+    fee_ratio,
+    tax_ratio,
+    ..Pool::default()
+  }
+}
+
+fn product(reserve_a: u64, reserve_b: u64) -> u128 {
+  reserve_a as u128 * reserve_b as u128
+}
+
+proptest! {
+  #[test]
+  fn curve_and_fee_keep_k(
+    reserve_a in 1..=u64::MAX,
+    reserve_b in 1..=u64::MAX,
+    bid_amount in any::<u64>(),
+    fee_ratio in 0..=MAX_FEE_RATIO,
+    tax_ratio in 0..=MAX_TAX_RATIO,
+  ) {
+    let pool = pool(reserve_a, reserve_b, fee_ratio, tax_ratio);
+    if let Some((ask_amount, new_reserve_a, new_reserve_b)) =
+      pool.curve(bid_amount, &pool.mint_a, &pool.mint_b)
+    {
+      prop_assert_eq!(new_reserve_a, reserve_a + bid_amount);
+      prop_assert_eq!(ask_amount + new_reserve_b, reserve_b);
+      // The new ask reserve is rounded down, so the product falls short by less than one unit
+      let k = product(reserve_a, reserve_b);
+      prop_assert!(product(new_reserve_a, new_reserve_b) + new_reserve_a as u128 > k);
+      let (amount, fee, tax) = pool.fee(ask_amount).unwrap();
+      prop_assert_eq!(amount + fee + tax, ask_amount);
+      prop_assert!(product(new_reserve_a, new_reserve_b + fee) + new_reserve_a as u128 > k);
+    }
+  }
+
+  #[test]
+  fn fee_is_bounded(
+    ask_amount in any::<u64>(),
+    fee_ratio in 0..=MAX_FEE_RATIO,
+    tax_ratio in 0..=MAX_TAX_RATIO,
+  ) {
+    let pool = pool(1, 1, fee_ratio, tax_ratio);
+    let (amount, fee, tax) = pool.fee(ask_amount).unwrap();
+    prop_assert_eq!(amount + fee + tax, ask_amount);
+    prop_assert!(fee as u128 <= ask_amount as u128 * MAX_FEE_RATIO as u128 / 1_000_000_000);
+    prop_assert!(tax as u128 <= (ask_amount - fee) as u128 * MAX_TAX_RATIO as u128 / 1_000_000_000);
+  }
+
+  #[test]
+  fn deposit_then_withdraw_is_bounded(
+    reserve_a in 1..=u64::MAX / 2,
+    reserve_b in 1..=u64::MAX / 2,
+    liquidity in 1..=u64::MAX / 2,
+    delta_a in any::<u64>(),
+    delta_b in any::<u64>(),
+  ) {
+    let before = pool(reserve_a, reserve_b, 0, 0);
+    if let Some((a, b, lpt, new_reserve_a, new_reserve_b, new_liquidity)) =
+      before.deposit(delta_a, delta_b, liquidity)
+    {
+      prop_assert!(a <= delta_a && b <= delta_b);
+      let after = pool(new_reserve_a, new_reserve_b, 0, 0);
+      let (withdrawn_a, withdrawn_b, _, reserve_a_again, reserve_b_again, liquidity_again) =
+        after.withdraw(lpt, new_liquidity).unwrap();
+      // Up to one unit of rounding per side
+      prop_assert!(withdrawn_a <= a.saturating_add(1));
+      prop_assert!(withdrawn_b <= b.saturating_add(1));
+      prop_assert!(reserve_a_again >= reserve_a - 1 && reserve_b_again >= reserve_b - 1);
+      prop_assert_eq!(liquidity_again, liquidity);
+    }
+  }
+
+  #[test]
+  fn withdraw_is_bounded(
+    reserve_a in any::<u64>(),
+    reserve_b in any::<u64>(),
+    liquidity in any::<u64>(),
+    lpt in any::<u64>(),
+  ) {
+    let pool = pool(reserve_a, reserve_b, 0, 0);
+    if let Some((delta_a, delta_b, _, new_reserve_a, new_reserve_b, new_liquidity)) =
+      pool.withdraw(lpt, liquidity)
+    {
+      prop_assert_eq!(delta_a + new_reserve_a, reserve_a);
+      prop_assert_eq!(delta_b + new_reserve_b, reserve_b);
+      prop_assert_eq!(new_liquidity + lpt, liquidity);
+      // Pro rata, up to one unit of rounding
+      let share = |reserve: u64| lpt as u128 * reserve as u128 + liquidity as u128;
+      prop_assert!((delta_a as u128 * liquidity as u128) < share(reserve_a));
+      prop_assert!((delta_b as u128 * liquidity as u128) < share(reserve_b));
+    }
+  }
+
+  #[test]
+  fn extract_keeps_the_ratio(
+    a in any::<u64>(),
+    b in any::<u64>(),
+    reserve_a in 1..=u64::MAX,
+    reserve_b in 1..=u64::MAX,
+  ) {
+    let (a_star, b_star) = extract(a, b, reserve_a, reserve_b).unwrap();
+    prop_assert!(a_star <= a && b_star <= b);
+    // Neither side exceeds the ratio of the other
+    prop_assert!(a_star as u128 * reserve_b as u128 <= b as u128 * reserve_a as u128);
+    prop_assert!(b_star as u128 * reserve_a as u128 <= a as u128 * reserve_b as u128);
+  }
+
+  #[test]
+  fn rake_is_bounded(
+    amount in any::<u64>(),
+    reserve_a in any::<u64>(),
+    reserve_b in any::<u64>(),
+    fee_ratio in any::<u64>(),
+    tax_ratio in any::<u64>(),
+  ) {
+    let pool = pool(reserve_a, reserve_b, fee_ratio, tax_ratio);
+    let mut data = vec![0; Pool::LEN];
+    Pool::pack(pool, &mut data).unwrap();
+    let (key, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+    let mut lamports = 0;
+    let pool_acc = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+    let raked = rake(amount, &pool.mint_a, &pool.mint_b, &pool_acc);
+    prop_assert_eq!(raked, split(amount, reserve_a, fee_ratio, tax_ratio));
+    if let Some(bid_amount) = raked {
+      prop_assert!(bid_amount <= amount);
+    }
+    // Within the bounds of the ratios, only an empty reserve has no answer
+    if reserve_a > 0 && fee_ratio <= MAX_FEE_RATIO && tax_ratio <= MAX_TAX_RATIO {
+      prop_assert!(raked.is_some());
+    }
+    // An unknown mint
+    prop_assert_eq!(rake(amount, &key, &pool.mint_b, &pool_acc), None);
+  }
+
+  #[test]
+  fn never_panics(
+    x in any::<[u64; 6]>(),
+  ) {
+    let [reserve_a, reserve_b, fee_ratio, tax_ratio, amount, liquidity] = x;
+    let pool = pool(reserve_a, reserve_b, fee_ratio, tax_ratio);
+    pool.curve(amount, &pool.mint_a, &pool.mint_b);
+    pool.curve(amount, &pool.mint_b, &pool.mint_a);
+    pool.fee(amount);
+    pool.discounted_fee(amount, fee_ratio, true);
+    pool.flash_fee(amount);
+    pool.referral(amount, tax_ratio);
+    pool.deposit(amount, liquidity, fee_ratio);
+    pool.withdraw(amount, liquidity);
+    extract(amount, liquidity, reserve_a, reserve_b);
+    split(amount, reserve_a, fee_ratio, tax_ratio);
+  }
+}
@@ -0,0 +1,259 @@
+mod common;
+
+use common::*;
+use main::{
+  error::AppError,
+  schema::{
+    pool::{Pool, MAX_FEE_RATIO, POOL_RESERVED, POOL_VERSION},
+    pool_state::PoolState,
+  },
+};
+use solana_program_test::tokio;
+use solana_sdk::{
+  program_pack::Pack,
+  pubkey::Pubkey,
+  signature::{Keypair, Signer},
+};
+
+const FEE_RATIO: u64 = 2_500_000;
+const TAX_RATIO: u64 = 500_000;
+const DELTA_A: u64 = 4_000_000_000;
+const DELTA_B: u64 = 9_000_000_000;
+
+///
+/// A bank with two mints funded to the payer, and the keys of a pool to create
+///
+async fn prepare() -> (Env, PoolKeys, Keypair, Keypair) {
+  let mut env = Env::start().await;
+  let payer = env.payer();
+  let mint_a = env.create_mint().await;
+  let mint_b = env.create_mint().await;
+  env.mint_to(&payer, &mint_a, DELTA_A).await;
+  env.mint_to(&payer, &mint_b, DELTA_B).await;
+  let pool = pool_keypair(&env.program_id);
+  let mint_lpt = Keypair::new();
+  let keys = PoolKeys::new(
+    &env.program_id,
+    pool.pubkey(),
+    mint_lpt.pubkey(),
+    mint_a,
+    mint_b,
+  );
+  (env, keys, pool, mint_lpt)
+}
+
+#[tokio::test]
+async fn initialize_pool_deposits_reserves() {
+  let (mut env, keys, pool, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let (owner, taxman) = (Pubkey::new_unique(), Pubkey::new_unique());
+  let ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &owner,
+    &taxman,
+    &keys,
+    DELTA_A,
+    DELTA_B,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  env.process(&[ix], &[&pool, &mint_lpt]).await.unwrap();
+
+  // The deposits moved to the treasuries, and the payer received sqrt(a*b) LP tokens
+  assert_eq!(env.balance(&payer, &keys.mint_a).await, 0);
+  assert_eq!(env.balance(&payer, &keys.mint_b).await, 0);
+  assert_eq!(env.token_balance(&keys.treasury_a).await, DELTA_A);
+  assert_eq!(env.token_balance(&keys.treasury_b).await, DELTA_B);
+  assert_eq!(env.balance(&payer, &keys.mint_lpt).await, 6_000_000_000);
+  assert_eq!(env.supply(&keys.mint_lpt).await, 6_000_000_000);
+
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(
+    pool_data,
+    Pool {
+      owner,
+      state: PoolState::Initialized,
+      mint_lpt: keys.mint_lpt,
+      taxman,
+      mint_a: keys.mint_a,
+      treasury_a: keys.treasury_a,
+      reserve_a: DELTA_A,
+      mint_b: keys.mint_b,
+      treasury_b: keys.treasury_b,
+      reserve_b: DELTA_B,
+      fee_ratio: FEE_RATIO,
+      tax_ratio: TAX_RATIO,
+      pauser: owner,
+      fee_manager: owner,
+      ..Pool::default()
+    }
+  );
+  let account = env
+    .context
+    .banks_client
+    .get_account(keys.pool)
+    .await
+    .unwrap()
+    .unwrap();
+  assert_eq!(account.data.len(), Pool::LEN);
+  // The version byte comes ahead of the referrers and the reserved bytes
+  assert_eq!(
+    account.data[Pool::LEN - 1 - 32 - POOL_RESERVED],
+    POOL_VERSION
+  );
+}
+
+#[tokio::test]
+async fn initialize_pool_unsigned_pool() {
+  let (mut env, keys, _, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let mut ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A,
+    DELTA_B,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  ix.accounts[2].is_signer = false;
+  let result = env.process(&[ix], &[&mint_lpt]).await;
+  assert_app_error(result, AppError::InvalidOwner);
+  assert!(!env.account_exists(&keys.pool).await);
+}
+
+#[tokio::test]
+async fn initialize_pool_invalid_proof() {
+  let (mut env, keys, pool, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let mut ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A,
+    DELTA_B,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  ix.accounts[6].pubkey = Pubkey::new_unique();
+  let result = env.process(&[ix], &[&pool, &mint_lpt]).await;
+  assert_app_error(result, AppError::InvalidLpProof);
+}
+
+#[tokio::test]
+async fn initialize_pool_same_mint() {
+  let (mut env, keys, pool, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let keys = PoolKeys::new(
+    &env.program_id,
+    keys.pool,
+    keys.mint_lpt,
+    keys.mint_a,
+    keys.mint_a,
+  );
+  let ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A,
+    DELTA_A,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  let result = env.process(&[ix], &[&pool, &mint_lpt]).await;
+  assert_app_error(result, AppError::SameMint);
+}
+
+#[tokio::test]
+async fn initialize_pool_zero_value() {
+  let (mut env, keys, pool, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A,
+    0,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  let result = env.process(&[ix], &[&pool, &mint_lpt]).await;
+  assert_app_error(result, AppError::ZeroValue);
+  assert_eq!(env.balance(&payer, &keys.mint_a).await, DELTA_A);
+}
+
+#[tokio::test]
+async fn initialize_pool_invalid_ratio() {
+  let (mut env, keys, pool, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A,
+    DELTA_B,
+    MAX_FEE_RATIO + 1,
+    TAX_RATIO,
+  );
+  let result = env.process(&[ix], &[&pool, &mint_lpt]).await;
+  assert_app_error(result, AppError::InvalidRatio);
+}
+
+#[tokio::test]
+async fn initialize_pool_already_initialized() {
+  let (mut env, keys, pool, mint_lpt) = prepare().await;
+  let payer = env.payer();
+  let ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A / 2,
+    DELTA_B / 2,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  env.process(&[ix], &[&pool, &mint_lpt]).await.unwrap();
+  // Again on the same pool, with a new LP mint
+  let mint_lpt = Keypair::new();
+  let keys = PoolKeys::new(
+    &env.program_id,
+    keys.pool,
+    mint_lpt.pubkey(),
+    keys.mint_a,
+    keys.mint_b,
+  );
+  let ix = initialize_pool(
+    &env.program_id,
+    &payer,
+    &payer,
+    &payer,
+    &keys,
+    DELTA_A / 2,
+    DELTA_B / 2,
+    FEE_RATIO,
+    TAX_RATIO,
+  );
+  let result = env.process(&[ix], &[&pool, &mint_lpt]).await;
+  assert_app_error(result, AppError::AlreadyInitialized);
+  // Nothing moved
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, DELTA_A / 2);
+  assert_eq!(pool_data.reserve_b, DELTA_B / 2);
+  assert_eq!(
+    env.balance(&payer, &keys.mint_a).await,
+    DELTA_A - DELTA_A / 2
+  );
+}
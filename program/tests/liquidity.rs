@@ -0,0 +1,364 @@
+mod common;
+
+use common::*;
+use main::{
+  error::AppError,
+  processor::add_sided_liquidity::split,
+  schema::{
+    pool::{extract, Pool},
+    pool_state::{PoolState, PAUSE_DEPOSIT, PAUSE_SWAP, PAUSE_WITHDRAW},
+    pool_trait::Exchange,
+  },
+};
+use solana_program_test::tokio;
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+use std::convert::TryInto;
+
+const FEE_RATIO: u64 = 2_500_000;
+const TAX_RATIO: u64 = 500_000;
+const RESERVE_A: u64 = 4_000_000_000;
+const RESERVE_B: u64 = 9_000_000_000;
+// sqrt(RESERVE_A * RESERVE_B)
+const LIQUIDITY: u64 = 6_000_000_000;
+
+async fn freeze(env: &mut Env, keys: &PoolKeys, mask: u8) {
+  let payer = env.payer();
+  let ix = freeze_pool(&env.program_id, &payer, &keys.pool, mask);
+  env.process(&[ix], &[]).await.unwrap();
+}
+
+#[tokio::test]
+async fn add_liquidity_balanced() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let ix = add_liquidity(&env.program_id, &trader.pubkey(), &keys, 400, 900);
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  assert_eq!(env.balance(&trader.pubkey(), &keys.mint_lpt).await, 600);
+  assert_eq!(env.supply(&keys.mint_lpt).await, LIQUIDITY + 600);
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_a).await,
+    RESERVE_A - 400
+  );
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_b).await,
+    RESERVE_B - 900
+  );
+  assert_eq!(env.token_balance(&keys.treasury_a).await, RESERVE_A + 400);
+  assert_eq!(env.token_balance(&keys.treasury_b).await, RESERVE_B + 900);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A + 400);
+  assert_eq!(pool_data.reserve_b, RESERVE_B + 900);
+  assert!(!pool_data.locked);
+}
+
+#[tokio::test]
+async fn add_liquidity_keeps_remainder() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let (a, b) = extract(1_000, 900, RESERVE_A, RESERVE_B).unwrap();
+  assert_eq!((a, b), (400, 900));
+  let ix = add_liquidity(&env.program_id, &trader.pubkey(), &keys, 1_000, 900);
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  // Only the balanced part is taken
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_a).await,
+    RESERVE_A - a
+  );
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_b).await,
+    RESERVE_B - b
+  );
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A + a);
+  assert_eq!(pool_data.reserve_b, RESERVE_B + b);
+}
+
+#[tokio::test]
+async fn add_liquidity_returns_amounts() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let ix = add_liquidity(&env.program_id, &trader.pubkey(), &keys, 1_000, 900);
+  let data = env
+    .process_with_return_data(&[ix], &[&trader])
+    .await
+    .unwrap();
+
+  // (a, b, lpt) of the balanced part, as little-endian u64s
+  assert_eq!(data.len(), 24);
+  let amounts: Vec<u64> = data
+    .chunks(8)
+    .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()))
+    .collect();
+  assert_eq!(amounts, vec![400, 900, 600]);
+  assert_eq!(env.balance(&trader.pubkey(), &keys.mint_lpt).await, 600);
+}
+
+#[tokio::test]
+async fn add_liquidity_errors() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let owner = trader.pubkey();
+
+  let mut ix = add_liquidity(&program_id, &owner, &keys, 400, 900);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+
+  let mut ix = add_liquidity(&program_id, &owner, &keys, 400, 900);
+  ix.accounts[1].pubkey = keys.mint_a;
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::IncorrectProgramId,
+  );
+
+  let mut ix = add_liquidity(&program_id, &owner, &keys, 400, 900);
+  ix.accounts[3].pubkey = Pubkey::new_unique();
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::UnmatchedPool,
+  );
+
+  let ix = add_liquidity(&program_id, &owner, &keys, 0, 0);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::ZeroValue);
+
+  let ix = add_liquidity(&program_id, &owner, &keys, u64::MAX, u64::MAX);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::Overflow);
+
+  freeze(&mut env, &keys, PAUSE_DEPOSIT).await;
+  let ix = add_liquidity(&program_id, &owner, &keys, 400, 900);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::FrozenPool);
+
+  // Nothing moved
+  assert_eq!(env.balance(&owner, &keys.mint_a).await, RESERVE_A);
+  assert_eq!(env.balance(&owner, &keys.mint_b).await, RESERVE_B);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A);
+  assert_eq!(pool_data.reserve_b, RESERVE_B);
+}
+
+#[tokio::test]
+async fn add_liquidity_locked_pool() {
+  let pool = Pubkey::new_unique();
+  let mut env = Env::start_with(|program_test, program_id| {
+    let pool_data = Pool {
+      owner: Pubkey::new_unique(),
+      state: PoolState::Initialized,
+      reserve_a: RESERVE_A,
+      reserve_b: RESERVE_B,
+      locked: true,
+      ..Pool::default()
+    };
+    program_test.add_account(pool, pool_account(program_id, &pool_data));
+  })
+  .await;
+  let trader = env.create_wallet().await;
+  let keys = PoolKeys {
+    pool,
+    treasurer: Pubkey::new_unique(),
+    mint_lpt: Pubkey::new_unique(),
+    mint_a: Pubkey::new_unique(),
+    treasury_a: Pubkey::new_unique(),
+    mint_b: Pubkey::new_unique(),
+    treasury_b: Pubkey::new_unique(),
+  };
+  let ix = add_liquidity(&env.program_id, &trader.pubkey(), &keys, 400, 900);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::LockedPool);
+}
+
+#[tokio::test]
+async fn remove_liquidity_to_fresh_accounts() {
+  let (mut env, keys, _) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let payer = env.payer();
+  // Move some LP tokens to a holder without A or B accounts
+  let holder = env.create_wallet().await;
+  let lpt = LIQUIDITY / 3;
+  let holder_lpt = env.create_ata(&holder.pubkey(), &keys.mint_lpt).await;
+  let ix = spl_token::instruction::transfer(
+    &spl_token::id(),
+    &spl_associated_token_account::get_associated_token_address(&payer, &keys.mint_lpt),
+    &holder_lpt,
+    &payer,
+    &[],
+    lpt,
+  )
+  .unwrap();
+  env.process(&[ix], &[]).await.unwrap();
+
+  let pool_data = env.pool(&keys.pool).await;
+  let (delta_a, delta_b, _, reserve_a, reserve_b, _) = pool_data.withdraw(lpt, LIQUIDITY).unwrap();
+  let ix = remove_liquidity(&env.program_id, &holder.pubkey(), &keys, lpt);
+  env.process(&[ix], &[&holder]).await.unwrap();
+
+  assert_eq!(env.balance(&holder.pubkey(), &keys.mint_lpt).await, 0);
+  assert_eq!(env.supply(&keys.mint_lpt).await, LIQUIDITY - lpt);
+  assert_eq!(env.balance(&holder.pubkey(), &keys.mint_a).await, delta_a);
+  assert_eq!(env.balance(&holder.pubkey(), &keys.mint_b).await, delta_b);
+  assert_eq!(env.token_balance(&keys.treasury_a).await, reserve_a);
+  assert_eq!(env.token_balance(&keys.treasury_b).await, reserve_b);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, reserve_a);
+  assert_eq!(pool_data.reserve_b, reserve_b);
+  assert!(!pool_data.locked);
+}
+
+#[tokio::test]
+async fn remove_liquidity_all() {
+  let (mut env, keys, _) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let payer = env.payer();
+  let ix = remove_liquidity(&env.program_id, &payer, &keys, LIQUIDITY);
+  env.process(&[ix], &[]).await.unwrap();
+
+  assert_eq!(env.supply(&keys.mint_lpt).await, 0);
+  assert_eq!(env.balance(&payer, &keys.mint_a).await, RESERVE_A);
+  assert_eq!(env.balance(&payer, &keys.mint_b).await, RESERVE_B);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, 0);
+  assert_eq!(pool_data.reserve_b, 0);
+}
+
+#[tokio::test]
+async fn remove_liquidity_errors() {
+  let (mut env, keys, _) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let stranger = env.create_wallet().await;
+
+  let mut ix = remove_liquidity(&program_id, &stranger.pubkey(), &keys, 1_000);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+
+  let mut ix = remove_liquidity(&program_id, &payer, &keys, 1_000);
+  ix.accounts[1].pubkey = keys.mint_lpt;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::IncorrectProgramId);
+
+  let mut ix = remove_liquidity(&program_id, &payer, &keys, 1_000);
+  ix.accounts[9].pubkey = keys.treasury_a;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::UnmatchedPool);
+
+  let ix = remove_liquidity(&program_id, &payer, &keys, 0);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::ZeroValue);
+
+  let ix = remove_liquidity(&program_id, &payer, &keys, LIQUIDITY + 1);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::Overflow);
+
+  freeze(&mut env, &keys, PAUSE_WITHDRAW).await;
+  let ix = remove_liquidity(&program_id, &payer, &keys, 1_000);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::FrozenPool);
+
+  // Deposits are still open
+  let ix = add_liquidity(&program_id, &payer, &keys, 0, 0);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::ZeroValue);
+
+  assert_eq!(env.balance(&payer, &keys.mint_lpt).await, LIQUIDITY);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A);
+  assert_eq!(pool_data.reserve_b, RESERVE_B);
+}
+
+#[tokio::test]
+async fn add_sided_liquidity_single_side() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let amount = 100_000_000;
+  let ix = add_sided_liquidity(&env.program_id, &trader.pubkey(), &keys, amount, 0);
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  // Part of A is swapped, then deposited with the received B
+  let bid_amount = split(amount, RESERVE_A, FEE_RATIO, TAX_RATIO).unwrap();
+  let before = Pool {
+    state: PoolState::Initialized,
+    mint_a: keys.mint_a,
+    reserve_a: RESERVE_A,
+    mint_b: keys.mint_b,
+    reserve_b: RESERVE_B,
+    fee_ratio: FEE_RATIO,
+    tax_ratio: TAX_RATIO,
+    ..Pool::default()
+  };
+  let (temp_ask_amount, reserve_a, temp_reserve_b) = before
+    .curve(bid_amount, &keys.mint_a, &keys.mint_b)
+    .unwrap();
+  let (ask_amount, fee, tax) = before.fee(temp_ask_amount).unwrap();
+  let swapped = Pool {
+    reserve_a,
+    reserve_b: temp_reserve_b + fee,
+    ..before
+  };
+  let (a, b, lpt, reserve_a, reserve_b, _) = swapped
+    .deposit(amount - bid_amount, ask_amount, LIQUIDITY)
+    .unwrap();
+
+  let trader = trader.pubkey();
+  assert_eq!(env.balance(&trader, &keys.mint_lpt).await, lpt);
+  assert_eq!(
+    env.balance(&trader, &keys.mint_a).await,
+    RESERVE_A - bid_amount - a
+  );
+  assert_eq!(
+    env.balance(&trader, &keys.mint_b).await,
+    RESERVE_B + ask_amount - b
+  );
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, reserve_a);
+  assert_eq!(pool_data.reserve_b, reserve_b);
+  assert_eq!(pool_data.tax_b, tax);
+  assert_eq!(pool_data.stats.swaps, 1);
+  // The leftover is dust
+  assert!(amount - bid_amount - a < amount / 1_000_000);
+  assert!(ask_amount - b < amount / 1_000_000);
+  assert_eq!(env.token_balance(&keys.treasury_a).await, reserve_a);
+  assert_eq!(env.token_balance(&keys.treasury_b).await, reserve_b + tax);
+}
+
+#[tokio::test]
+async fn add_sided_liquidity_errors() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let owner = trader.pubkey();
+
+  let mut ix = add_sided_liquidity(&program_id, &owner, &keys, 1_000, 0);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+
+  let ix = add_sided_liquidity(&program_id, &owner, &keys, 0, 0);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::ZeroValue);
+
+  let mut ix = add_sided_liquidity(&program_id, &owner, &keys, 1_000, 0);
+  ix.accounts[8].pubkey = keys.mint_a;
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::UnmatchedPool,
+  );
+
+  // The swap of the remainder is paused
+  freeze(&mut env, &keys, PAUSE_SWAP).await;
+  let ix = add_sided_liquidity(&program_id, &owner, &keys, 1_000_000, 0);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::FrozenPool);
+  // But a balanced deposit needs no swap
+  let ix = add_sided_liquidity(&program_id, &owner, &keys, 400, 900);
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&owner, &keys.mint_lpt).await, 600);
+
+  freeze(&mut env, &keys, PAUSE_DEPOSIT).await;
+  let ix = add_sided_liquidity(&program_id, &owner, &keys, 400, 900);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::FrozenPool);
+
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A + 400);
+  assert_eq!(pool_data.reserve_b, RESERVE_B + 900);
+}
+
+#[tokio::test]
+async fn remove_liquidity_round_trip() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let ix = add_liquidity(&env.program_id, &trader.pubkey(), &keys, 400, 900);
+  env.process(&[ix], &[&trader]).await.unwrap();
+  // Withdrawing more than held fails in the token program, leaving the pool unlocked
+  let ix = remove_liquidity(&env.program_id, &trader.pubkey(), &keys, 601);
+  assert!(env.process(&[ix], &[&trader]).await.is_err());
+  let ix = remove_liquidity(&env.program_id, &trader.pubkey(), &keys, 600);
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&trader.pubkey(), &keys.mint_a).await, RESERVE_A);
+  assert_eq!(env.balance(&trader.pubkey(), &keys.mint_b).await, RESERVE_B);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A);
+  assert_eq!(pool_data.reserve_b, RESERVE_B);
+  assert!(!pool_data.locked);
+}
@@ -0,0 +1,483 @@
+mod common;
+
+use common::*;
+use main::{
+  error::AppError,
+  schema::{
+    pool::{Pool, MAX_FEE_RATIO, MAX_REFERRAL_RATIO, MAX_TAX_RATIO},
+    pool_state::{PoolState, PAUSE_ALL, PAUSE_DEPOSIT, PAUSE_SWAP, PAUSE_WITHDRAW},
+    pool_trait::Operation,
+  },
+};
+use solana_program_test::tokio;
+use solana_sdk::{
+  pubkey::Pubkey,
+  signature::{Keypair, Signer},
+};
+use spl_associated_token_account::get_associated_token_address;
+
+const FEE_RATIO: u64 = 2_500_000;
+const TAX_RATIO: u64 = 500_000;
+const RESERVE_A: u64 = 4_000_000_000;
+const RESERVE_B: u64 = 9_000_000_000;
+
+#[tokio::test]
+async fn freeze_and_thaw() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let owner = trader.pubkey();
+
+  let ix = freeze_pool(&program_id, &payer, &keys.pool, PAUSE_SWAP | PAUSE_WITHDRAW);
+  env.process(&[ix], &[]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.paused, PAUSE_SWAP | PAUSE_WITHDRAW);
+  assert!(pool_data.is_paused(PAUSE_SWAP));
+  assert!(!pool_data.is_paused(PAUSE_DEPOSIT));
+
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    1_000,
+    0,
+    None,
+  );
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::FrozenPool);
+  // Deposits are still open
+  let ix = add_liquidity(&program_id, &owner, &keys, 400, 900);
+  env.process(&[ix], &[&trader]).await.unwrap();
+  let ix = remove_liquidity(&program_id, &owner, &keys, 600);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::FrozenPool);
+
+  let ix = thaw_pool(&program_id, &payer, &keys.pool, PAUSE_WITHDRAW);
+  env.process(&[ix], &[]).await.unwrap();
+  assert_eq!(env.pool(&keys.pool).await.paused, PAUSE_SWAP);
+  let ix = remove_liquidity(&program_id, &owner, &keys, 600);
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&owner, &keys.mint_lpt).await, 0);
+  assert_eq!(env.balance(&owner, &keys.mint_a).await, RESERVE_A);
+
+  let ix = thaw_pool(&program_id, &payer, &keys.pool, PAUSE_ALL);
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    1_000,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.paused, 0);
+  assert_eq!(pool_data.state, PoolState::Initialized);
+  assert_eq!(pool_data.reserve_a, RESERVE_A + 1_000);
+}
+
+#[tokio::test]
+async fn freeze_and_thaw_errors() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+
+  // Only the pauser freezes
+  let ix = freeze_pool(&program_id, &trader.pubkey(), &keys.pool, PAUSE_ALL);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidOwner);
+  let ix = freeze_pool(&program_id, &payer, &keys.mint_lpt, PAUSE_ALL);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::IncorrectProgramId);
+
+  // A dedicated pauser freezes, but only the owner thaws
+  let pauser = env.create_wallet().await;
+  let ix = transfer_pauser(&program_id, &payer, &keys.pool, &pauser.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = freeze_pool(&program_id, &payer, &keys.pool, PAUSE_ALL);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let mut ix = freeze_pool(&program_id, &pauser.pubkey(), &keys.pool, PAUSE_ALL);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = freeze_pool(&program_id, &pauser.pubkey(), &keys.pool, PAUSE_ALL);
+  env.process(&[ix], &[&pauser]).await.unwrap();
+  let ix = thaw_pool(&program_id, &pauser.pubkey(), &keys.pool, PAUSE_ALL);
+  assert_app_error(env.process(&[ix], &[&pauser]).await, AppError::InvalidOwner);
+  assert_eq!(env.pool(&keys.pool).await.paused, PAUSE_ALL);
+
+  let ix = thaw_pool(&program_id, &payer, &keys.pool, PAUSE_ALL);
+  env.process(&[ix], &[]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.paused, 0);
+  assert_eq!(pool_data.pauser, pauser.pubkey());
+  assert_eq!(pool_data.owner, payer);
+}
+
+#[tokio::test]
+async fn freeze_locked_pool() {
+  let pool = Pubkey::new_unique();
+  let pauser = Pubkey::new_unique();
+  let mut env = Env::start_with(|program_test, program_id| {
+    let pool_data = Pool {
+      owner: pauser,
+      state: PoolState::Initialized,
+      pauser,
+      locked: true,
+      ..Pool::default()
+    };
+    program_test.add_account(pool, pool_account(program_id, &pool_data));
+  })
+  .await;
+  let mut ix = freeze_pool(&env.program_id, &pauser, &pool, PAUSE_ALL);
+  // Any signer gets as far as the lock, which is checked before the role
+  ix.accounts[0].pubkey = env.payer();
+  assert_app_error(env.process(&[ix], &[]).await, AppError::LockedPool);
+}
+
+#[tokio::test]
+async fn update_fee_and_referral() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &keys.pool,
+    MAX_FEE_RATIO,
+    MAX_TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO),
+  );
+  env.process(&[ix], &[]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.fee_ratio, MAX_FEE_RATIO);
+  assert_eq!(pool_data.tax_ratio, MAX_TAX_RATIO);
+  assert_eq!(pool_data.max_referral_ratio, MAX_REFERRAL_RATIO);
+
+  // The referral ratio is kept when omitted
+  let ix = update_fee(&program_id, &payer, &keys.pool, 0, 0, None);
+  env.process(&[ix], &[]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.fee_ratio, 0);
+  assert_eq!(pool_data.tax_ratio, 0);
+  assert_eq!(pool_data.max_referral_ratio, MAX_REFERRAL_RATIO);
+
+  // A fee-free pool follows the bare curve
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    4_000_000_000,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_b).await,
+    RESERVE_B + RESERVE_B / 2
+  );
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A * 2);
+  assert_eq!(pool_data.reserve_b, RESERVE_B / 2);
+  assert_eq!(pool_data.tax_b, 0);
+}
+
+#[tokio::test]
+async fn update_fee_errors() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &keys.pool,
+    MAX_FEE_RATIO + 1,
+    TAX_RATIO,
+    None,
+  );
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidRatio);
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &keys.pool,
+    FEE_RATIO,
+    MAX_TAX_RATIO + 1,
+    None,
+  );
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidRatio);
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &keys.pool,
+    FEE_RATIO,
+    TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO + 1),
+  );
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidRatio);
+
+  // Only the fee manager updates the fee
+  let ix = update_fee(&program_id, &trader.pubkey(), &keys.pool, 0, 0, None);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidOwner);
+  let ix = update_fee(&program_id, &payer, &keys.mint_lpt, 0, 0, None);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::IncorrectProgramId);
+
+  // Handing the fee manager over revokes the owner
+  let ix = transfer_fee_manager(&program_id, &payer, &keys.pool, &trader.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = update_fee(&program_id, &payer, &keys.pool, 0, 0, None);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let mut ix = update_fee(&program_id, &trader.pubkey(), &keys.pool, 0, 0, None);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = update_fee(&program_id, &trader.pubkey(), &keys.pool, 1, 2, None);
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.fee_manager, trader.pubkey());
+  assert_eq!(pool_data.fee_ratio, 1);
+  assert_eq!(pool_data.tax_ratio, 2);
+  assert_eq!(pool_data.max_referral_ratio, 0);
+}
+
+#[tokio::test]
+async fn transfer_taxman_and_claim() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let owner = env.create_wallet().await;
+  let taxman = env.create_wallet().await;
+  // An owner other than the payer, so that it can be left out of the signers
+  let ix = transfer_ownership(&program_id, &payer, &keys.pool, &owner.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+
+  let ix = transfer_taxman(&program_id, &trader.pubkey(), &keys.pool, &trader.pubkey());
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidOwner);
+  let mut ix = transfer_taxman(&program_id, &owner.pubkey(), &keys.pool, &taxman.pubkey());
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = transfer_taxman(&program_id, &owner.pubkey(), &keys.mint_a, &taxman.pubkey());
+  assert_app_error(
+    env.process(&[ix], &[&owner]).await,
+    AppError::IncorrectProgramId,
+  );
+
+  let ix = transfer_taxman(&program_id, &owner.pubkey(), &keys.pool, &taxman.pubkey());
+  env.process(&[ix], &[&owner]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.taxman, taxman.pubkey());
+  assert_eq!(pool_data.owner, owner.pubkey());
+
+  // The tax of a swap goes to the new taxman
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    100_000_000,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+  let tax = env.pool(&keys.pool).await.tax_b;
+  assert!(tax > 0);
+  let ix = claim_tax(&program_id, &payer, &payer, &keys, &[]);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = claim_tax(&program_id, &payer, &taxman.pubkey(), &keys, &[]);
+  env.process(&[ix], &[&taxman]).await.unwrap();
+  assert_eq!(env.balance(&taxman.pubkey(), &keys.mint_b).await, tax);
+  assert_eq!(env.pool(&keys.pool).await.tax_b, 0);
+}
+
+#[tokio::test]
+async fn claim_tax_by_multisig() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let multisig = Keypair::new();
+  let members = [env.create_wallet().await, env.create_wallet().await];
+  let member_keys: Vec<Pubkey> = members.iter().map(|member| member.pubkey()).collect();
+  let ix = initialize_multisig(&program_id, &payer, &multisig.pubkey(), 2, &member_keys);
+  env.process(&[ix], &[&multisig]).await.unwrap();
+  let ix = transfer_taxman(&program_id, &payer, &keys.pool, &multisig.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+
+  let ix = swap(
+    &program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    100_000_000,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+  let tax = env.pool(&keys.pool).await.tax_b;
+  assert!(tax > 0);
+
+  // The multisig holds no lamports, the payer funds its token accounts
+  let ix = claim_tax(
+    &program_id,
+    &payer,
+    &multisig.pubkey(),
+    &keys,
+    &member_keys[..1],
+  );
+  assert_app_error(
+    env.process(&[ix], &[&members[0]]).await,
+    AppError::NotEnoughSigners,
+  );
+  let ix = claim_tax(&program_id, &payer, &multisig.pubkey(), &keys, &member_keys);
+  env
+    .process(&[ix], &[&members[0], &members[1]])
+    .await
+    .unwrap();
+  assert_eq!(env.balance(&multisig.pubkey(), &keys.mint_b).await, tax);
+  assert_eq!(env.pool(&keys.pool).await.tax_b, 0);
+}
+
+#[tokio::test]
+async fn close_pool_by_multisig() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let multisig = Keypair::new();
+  let members = [env.create_wallet().await, env.create_wallet().await];
+  let member_keys: Vec<Pubkey> = members.iter().map(|member| member.pubkey()).collect();
+  let ix = initialize_multisig(&program_id, &payer, &multisig.pubkey(), 2, &member_keys);
+  env.process(&[ix], &[&multisig]).await.unwrap();
+  let ix = transfer_ownership(&program_id, &payer, &keys.pool, &multisig.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+
+  let lpt = env.balance(&payer, &keys.mint_lpt).await;
+  let ix = remove_liquidity(&program_id, &payer, &keys, lpt);
+  env.process(&[ix], &[]).await.unwrap();
+  // A donation leaves dust to sweep into the accounts of the multisig
+  let ix = spl_token::instruction::transfer(
+    &spl_token::id(),
+    &get_associated_token_address(&trader.pubkey(), &keys.mint_a),
+    &keys.treasury_a,
+    &trader.pubkey(),
+    &[],
+    1_000,
+  )
+  .unwrap();
+  env.process(&[ix], &[&trader]).await.unwrap();
+  let dust = env.token_balance(&keys.treasury_a).await;
+
+  // The multisig holds no lamports, the payer funds its token accounts
+  let ix = close_pool(
+    &program_id,
+    &payer,
+    &multisig.pubkey(),
+    &keys,
+    &multisig.pubkey(),
+    &member_keys,
+  );
+  env
+    .process(&[ix], &[&members[0], &members[1]])
+    .await
+    .unwrap();
+  assert_eq!(env.balance(&multisig.pubkey(), &keys.mint_a).await, dust);
+}
+
+#[tokio::test]
+async fn multisig_duplicate_signer() {
+  let (mut env, keys, _) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let member = env.create_wallet().await;
+  let other = env.create_wallet().await;
+
+  // A key registered twice is rejected
+  let multisig = Keypair::new();
+  let ix = initialize_multisig(
+    &program_id,
+    &payer,
+    &multisig.pubkey(),
+    2,
+    &[member.pubkey(), member.pubkey(), other.pubkey()],
+  );
+  assert_app_error(
+    env.process(&[ix], &[&multisig]).await,
+    AppError::InvalidMultisig,
+  );
+
+  // A key passed twice only counts once towards m
+  let ix = initialize_multisig(
+    &program_id,
+    &payer,
+    &multisig.pubkey(),
+    2,
+    &[member.pubkey(), other.pubkey()],
+  );
+  env.process(&[ix], &[&multisig]).await.unwrap();
+  let ix = transfer_ownership(&program_id, &payer, &keys.pool, &multisig.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = transfer_ownership_multisig(
+    &program_id,
+    &multisig.pubkey(),
+    &keys.pool,
+    &member.pubkey(),
+    &[member.pubkey(), member.pubkey()],
+  );
+  assert_app_error(
+    env.process(&[ix], &[&member]).await,
+    AppError::NotEnoughSigners,
+  );
+  assert_eq!(env.pool(&keys.pool).await.owner, multisig.pubkey());
+}
+
+#[tokio::test]
+async fn transfer_ownership_and_back() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let new_owner = env.create_wallet().await;
+
+  let ix = transfer_ownership(&program_id, &trader.pubkey(), &keys.pool, &trader.pubkey());
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidOwner);
+  let ix = transfer_ownership(&program_id, &payer, &keys.mint_b, &new_owner.pubkey());
+  assert_app_error(env.process(&[ix], &[]).await, AppError::IncorrectProgramId);
+
+  let ix = transfer_ownership(&program_id, &payer, &keys.pool, &new_owner.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.owner, new_owner.pubkey());
+  // The other roles stay
+  assert_eq!(pool_data.pauser, payer);
+  assert_eq!(pool_data.fee_manager, payer);
+  assert_eq!(pool_data.taxman, payer);
+
+  // The previous owner lost the owner's rights
+  let ix = transfer_taxman(&program_id, &payer, &keys.pool, &payer);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = thaw_pool(&program_id, &payer, &keys.pool, PAUSE_ALL);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = transfer_ownership(&program_id, &payer, &keys.pool, &payer);
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+
+  // The new owner has them
+  let ix = transfer_taxman(
+    &program_id,
+    &new_owner.pubkey(),
+    &keys.pool,
+    &new_owner.pubkey(),
+  );
+  env.process(&[ix], &[&new_owner]).await.unwrap();
+  let mut ix = transfer_ownership(&program_id, &new_owner.pubkey(), &keys.pool, &payer);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+  let ix = transfer_ownership(&program_id, &new_owner.pubkey(), &keys.pool, &payer);
+  env.process(&[ix], &[&new_owner]).await.unwrap();
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.owner, payer);
+  assert_eq!(pool_data.taxman, new_owner.pubkey());
+  assert_eq!(pool_data.reserve_a, RESERVE_A);
+  assert_eq!(pool_data.reserve_b, RESERVE_B);
+}
@@ -0,0 +1,282 @@
+use main::{
+  error::AppError,
+  instruction::AppInstruction,
+  router::Router,
+  schema::{
+    pool::Pool,
+    pool_state::{PoolState, PAUSE_SWAP},
+    pool_trait::Exchange,
+  },
+};
+use solana_program::{program_error::ProgramError, program_pack::Pack, pubkey::Pubkey};
+
+fn program_id() -> Pubkey {
+  Pubkey::new_from_array([7; 32])
+}
+
+///
+/// Pool addresses are chosen so that the treasurer exists
+///
+fn pool_id() -> Pubkey {
+  loop {
+    let pool_id = Pubkey::new_unique();
+    if Pubkey::create_program_address(&[&pool_id.to_bytes()], &program_id()).is_ok() {
+      return pool_id;
+    }
+  }
+}
+
+///
+/// Pool snapshot as decoded from the account data
+///
+fn snapshot(
+  mint_a: &Pubkey,
+  reserve_a: u64,
+  mint_b: &Pubkey,
+  reserve_b: u64,
+  fee_ratio: u64,
+  tax_ratio: u64,
+) -> (Pubkey, Pool) {
+  let pool = Pool {
+    owner: Pubkey::new_unique(),
+    state: PoolState::Initialized,
+    mint_lpt: Pubkey::new_unique(),
+    taxman: Pubkey::new_unique(),
+    mint_a: *mint_a,
+    treasury_a: Pubkey::new_unique(),
+    reserve_a,
+    mint_b: *mint_b,
+    treasury_b: Pubkey::new_unique(),
+    reserve_b,
+    fee_ratio,
+    tax_ratio,
+    ..Pool::default()
+  };
+  let mut data = vec![0u8; Pool::LEN];
+  Pool::pack(pool, &mut data).unwrap();
+  (pool_id(), Pool::unpack(&data).unwrap())
+}
+
+struct Fixture {
+  sol: Pubkey,
+  usdc: Pubkey,
+  sen: Pubkey,
+  pools: Vec<(Pubkey, Pool)>,
+}
+
+///
+/// A shallow direct SOL/USDC pool, a deep SOL/SEN/USDC detour,
+/// and a second SOL/USDC pool of the same depth
+///
+fn fixture() -> Fixture {
+  let sol = Pubkey::new_unique();
+  let usdc = Pubkey::new_unique();
+  let sen = Pubkey::new_unique();
+  let pools = vec![
+    snapshot(&sol, 1_000_000, &usdc, 150_000_000, 2500000, 0),
+    snapshot(&sol, 100_000_000, &sen, 1_000_000_000, 2500000, 0),
+    snapshot(&sen, 1_000_000_000, &usdc, 15_000_000_000, 2500000, 0),
+    snapshot(&sol, 1_000_000, &usdc, 150_000_000, 2500000, 0),
+  ];
+  Fixture {
+    sol,
+    usdc,
+    sen,
+    pools,
+  }
+}
+
+#[test]
+fn quote_matches_on_chain_math() {
+  let Fixture {
+    sol, usdc, pools, ..
+  } = fixture();
+  let router = Router::new(&pools);
+  let (pool_id, pool) = pools[0];
+  let path = router.quote(&[(pool_id, sol, usdc)], 10_000).unwrap();
+  let (temp_ask_amount, _, _) = pool.curve(10_000, &sol, &usdc).unwrap();
+  let (ask_amount, _, _) = pool.fee(temp_ask_amount).unwrap();
+  assert_eq!(path.ask_amount(), ask_amount);
+  assert_eq!(path.bid_amount(), 10_000);
+}
+
+#[test]
+fn quote_includes_tax() {
+  let sol = Pubkey::new_unique();
+  let usdc = Pubkey::new_unique();
+  let untaxed = snapshot(&sol, 1_000_000, &usdc, 150_000_000, 2500000, 0);
+  let taxed = snapshot(&sol, 1_000_000, &usdc, 150_000_000, 2500000, 100000000);
+  let untaxed_path = Router::new(&[untaxed])
+    .quote(&[(untaxed.0, sol, usdc)], 10_000)
+    .unwrap();
+  let taxed_path = Router::new(&[taxed])
+    .quote(&[(taxed.0, sol, usdc)], 10_000)
+    .unwrap();
+  assert!(taxed_path.ask_amount() < untaxed_path.ask_amount());
+}
+
+#[test]
+fn best_path_prefers_the_deeper_detour() {
+  let Fixture {
+    sol,
+    usdc,
+    sen,
+    pools,
+  } = fixture();
+  let router = Router::new(&pools);
+  // Direct only
+  let direct = router.best_path(&sol, &usdc, 500_000, 1).unwrap();
+  assert_eq!(direct.hops.len(), 1);
+  // Up to 2 hops
+  let best = router.best_path(&sol, &usdc, 500_000, 2).unwrap();
+  assert_eq!(best.hops.len(), 2);
+  assert_eq!(best.hops[0].ask_mint, sen);
+  assert_eq!(best.hops[1].bid_amount, best.hops[0].ask_amount);
+  assert!(best.ask_amount() > direct.ask_amount());
+}
+
+#[test]
+fn best_path_skips_paused_pools() {
+  let Fixture {
+    sol,
+    usdc,
+    mut pools,
+    ..
+  } = fixture();
+  pools[1].1.paused = PAUSE_SWAP;
+  let router = Router::new(&pools);
+  let best = router.best_path(&sol, &usdc, 500_000, 2).unwrap();
+  assert_eq!(best.hops.len(), 1);
+}
+
+#[test]
+fn best_split_beats_single_path() {
+  let Fixture {
+    sol, usdc, pools, ..
+  } = fixture();
+  let router = Router::new(&[pools[0], pools[3]]);
+  let single = router.best_path(&sol, &usdc, 100_000, 1).unwrap();
+  let split = router.best_split(&sol, &usdc, 100_000, 1, 2, 10).unwrap();
+  assert_eq!(split.len(), 2);
+  let bid_amount: u64 = split.iter().map(|path| path.bid_amount()).sum();
+  let ask_amount: u64 = split.iter().map(|path| path.ask_amount()).sum();
+  assert_eq!(bid_amount, 100_000);
+  assert!(ask_amount > single.ask_amount());
+}
+
+#[test]
+fn route_accounts_are_continuous() {
+  let Fixture {
+    sol, usdc, pools, ..
+  } = fixture();
+  let program_id = program_id();
+  let owner = Pubkey::new_unique();
+  let router = Router::new(&pools);
+  let path = router.best_path(&sol, &usdc, 500_000, 2).unwrap();
+  let accounts = router.route_accounts(&program_id, &owner, &path).unwrap();
+  assert_eq!(accounts.len(), 5 + 8 * path.hops.len());
+  assert_eq!(accounts[0].pubkey, owner);
+  assert!(accounts[0].is_signer);
+  for (i, hop) in path.hops.iter().enumerate() {
+    let hop_accounts = &accounts[5 + 8 * i..13 + 8 * i];
+    assert_eq!(hop_accounts[0].pubkey, hop.pool);
+    assert_eq!(hop_accounts[2].pubkey, hop.bid_mint);
+    assert_eq!(hop_accounts[5].pubkey, hop.ask_mint);
+    let treasurer = Pubkey::create_program_address(&[&hop.pool.to_bytes()], &program_id).unwrap();
+    assert_eq!(hop_accounts[7].pubkey, treasurer);
+    // The ask of the previous hop is the bid of this hop
+    if i > 0 {
+      let prev_accounts = &accounts[8 * i - 3..5 + 8 * i];
+      assert_eq!(prev_accounts[4].pubkey, hop_accounts[1].pubkey);
+      assert_eq!(prev_accounts[5].pubkey, hop_accounts[2].pubkey);
+    }
+  }
+}
+
+#[test]
+fn route_instruction_layout() {
+  let Fixture {
+    sol, usdc, pools, ..
+  } = fixture();
+  let program_id = program_id();
+  let owner = Pubkey::new_unique();
+  let router = Router::new(&pools);
+  let path = router.best_path(&sol, &usdc, 500_000, 2).unwrap();
+  let ix = router
+    .route_instruction(&program_id, &owner, &path, 1)
+    .unwrap();
+  // Tag, amount and limit, without hop limits
+  let mut data = vec![8];
+  data.extend_from_slice(&500_000u64.to_le_bytes());
+  data.extend_from_slice(&1u64.to_le_bytes());
+  assert_eq!(ix.data, data);
+  assert_eq!(ix.accounts.len(), 5 + 8 * path.hops.len());
+}
+
+#[test]
+fn split_route_instruction_layout() {
+  let Fixture {
+    sol, usdc, pools, ..
+  } = fixture();
+  let program_id = program_id();
+  let owner = Pubkey::new_unique();
+  let router = Router::new(&[pools[0], pools[3]]);
+  let split = router.best_split(&sol, &usdc, 100_000, 1, 2, 10).unwrap();
+  let ix = router
+    .split_route_instruction(&program_id, &owner, &split, 1)
+    .unwrap();
+  assert_eq!(ix.data[0], 19);
+  assert_eq!(ix.data.len(), 1 + 8 + 9 * split.len());
+  assert_eq!(ix.accounts.len(), 5 + 8 * split.len());
+}
+
+#[test]
+fn malformed_instruction_data() {
+  let invalid = Err(ProgramError::from(AppError::InvalidInstruction));
+  // SplitRoute with a truncated path
+  let mut data = vec![19];
+  data.extend_from_slice(&1u64.to_le_bytes());
+  data.extend_from_slice(&[1; 5]);
+  assert_eq!(AppInstruction::unpack(&data), invalid);
+  // InitializeDistribution with an odd number of share bytes
+  assert_eq!(AppInstruction::unpack(&[23, 1, 2, 3]), invalid);
+  // Route with a truncated hop limit
+  let mut data = vec![8];
+  data.extend_from_slice(&[0; 24]);
+  data.extend_from_slice(&[0; 3]);
+  assert_eq!(AppInstruction::unpack(&data), invalid);
+}
+
+#[test]
+fn route_data_layouts() {
+  let values = |tag: u8, values: &[u64]| {
+    let mut data = vec![tag];
+    for value in values {
+      data.extend_from_slice(&value.to_le_bytes());
+    }
+    data
+  };
+  // Route reads the hop limits right after the limit
+  assert_eq!(
+    AppInstruction::unpack(&values(8, &[100, 90, 95, 90])),
+    Ok(AppInstruction::Route {
+      amount: 100,
+      limit: 90,
+      hop_limits: vec![95, 90],
+    })
+  );
+  // RouteWithReferral reads the referral ratio first
+  assert_eq!(
+    AppInstruction::unpack(&values(36, &[100, 90, 5, 95, 90])),
+    Ok(AppInstruction::RouteWithReferral {
+      amount: 100,
+      limit: 90,
+      referral_ratio: 5,
+      hop_limits: vec![95, 90],
+    })
+  );
+  assert_eq!(
+    AppInstruction::unpack(&values(36, &[100, 90])),
+    Err(ProgramError::from(AppError::InvalidInstruction))
+  );
+}
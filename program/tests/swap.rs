@@ -0,0 +1,896 @@
+mod common;
+
+use common::*;
+use main::{
+  error::AppError,
+  router::Router,
+  schema::{
+    pool::{Pool, MAX_REFERRAL_RATIO},
+    pool_state::{PoolState, PAUSE_DEPOSIT, PAUSE_SWAP},
+    pool_trait::Exchange,
+    stake::HOLDING_SLOTS,
+  },
+};
+use solana_program_test::tokio;
+use solana_sdk::{
+  instruction::AccountMeta,
+  pubkey::Pubkey,
+  signature::{Keypair, Signer},
+  system_program, sysvar,
+};
+use spl_associated_token_account::get_associated_token_address;
+
+const FEE_RATIO: u64 = 2_500_000;
+const TAX_RATIO: u64 = 500_000;
+const RESERVE_A: u64 = 4_000_000_000;
+const RESERVE_B: u64 = 9_000_000_000;
+const RESERVE_C: u64 = 1_000_000_000;
+const STAKE: u64 = 1_000_000;
+const DISCOUNT: u64 = 500_000_000; // 50%
+
+///
+/// Ask amount, fee and tax of swapping amount of bid, and the pool afterwards
+///
+fn quote(pool: &Pool, bid_mint: &Pubkey, ask_mint: &Pubkey, amount: u64) -> (u64, u64, u64, Pool) {
+  let (temp_ask_amount, new_bid_reserve, temp_new_ask_reserve) =
+    pool.curve(amount, bid_mint, ask_mint).unwrap();
+  let (ask_amount, fee, tax) = pool.fee(temp_ask_amount).unwrap();
+  let mut next = *pool;
+  let (bid_code, _) = pool.get_reserve(bid_mint).unwrap();
+  let (reserve_a, reserve_b) = match bid_code {
+    0 => (new_bid_reserve, temp_new_ask_reserve + fee),
+    _ => (temp_new_ask_reserve + fee, new_bid_reserve),
+  };
+  next.reserve_a = reserve_a;
+  next.reserve_b = reserve_b;
+  (ask_amount, fee, tax, next)
+}
+
+///
+/// A trader holding A only
+///
+async fn newcomer(env: &mut Env, keys: &PoolKeys) -> Keypair {
+  let trader = env.create_wallet().await;
+  env.mint_to(&trader.pubkey(), &keys.mint_a, RESERVE_A).await;
+  trader
+}
+
+///
+/// Register the referrers of the pool, under the payer
+///
+async fn register_referrers(env: &mut Env, pool: &Pubkey, members: &[Pubkey]) -> Pubkey {
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let referrers = Keypair::new();
+  let ix = initialize_referrers(&program_id, &payer, &referrers.pubkey(), &payer, members);
+  env.process(&[ix], &[&referrers]).await.unwrap();
+  let ix = set_referrers(&program_id, &payer, pool, &referrers.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  referrers.pubkey()
+}
+
+#[tokio::test]
+async fn swap_to_fresh_account() {
+  let (mut env, keys, _) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let trader = newcomer(&mut env, &keys).await;
+  let before = env.pool(&keys.pool).await;
+  let amount = 100_000_000;
+  let (ask_amount, fee, tax, after) = quote(&before, &keys.mint_a, &keys.mint_b, amount);
+  let ix = swap(
+    &env.program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    amount,
+    ask_amount,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_a).await,
+    RESERVE_A - amount
+  );
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_b).await,
+    ask_amount
+  );
+  assert_eq!(
+    env.token_balance(&keys.treasury_a).await,
+    RESERVE_A + amount
+  );
+  assert_eq!(
+    env.token_balance(&keys.treasury_b).await,
+    RESERVE_B - ask_amount
+  );
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, after.reserve_a);
+  assert_eq!(pool_data.reserve_b, after.reserve_b);
+  // The tax stays in the treasury until claimed
+  assert_eq!(pool_data.tax_a, 0);
+  assert_eq!(pool_data.tax_b, tax);
+  assert_eq!(pool_data.reserve_b + tax, RESERVE_B - ask_amount);
+  assert_eq!(pool_data.stats.swaps, 1);
+  assert_eq!(pool_data.stats.volume_a, amount as u128);
+  assert_eq!(pool_data.stats.volume_b, ask_amount as u128);
+  assert_eq!(pool_data.stats.fee_b, fee as u128);
+  assert_eq!(pool_data.stats.tax_b, tax as u128);
+  assert!(!pool_data.locked);
+  // The product never decreases
+  assert!(
+    pool_data.reserve_a as u128 * pool_data.reserve_b as u128
+      >= RESERVE_A as u128 * RESERVE_B as u128
+  );
+}
+
+#[tokio::test]
+async fn swap_back_and_forth() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let owner = trader.pubkey();
+  let amount = 100_000_000;
+  let ix = swap(
+    &env.program_id,
+    &owner,
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    amount,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+  let received = env.balance(&owner, &keys.mint_b).await - RESERVE_B;
+  let before = env.pool(&keys.pool).await;
+  let (ask_amount, _, tax, after) = quote(&before, &keys.mint_b, &keys.mint_a, received);
+  let ix = swap(
+    &env.program_id,
+    &owner,
+    &keys,
+    &keys.mint_b,
+    &keys.mint_a,
+    received,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  // The round trip costs the fees and taxes
+  assert_eq!(
+    env.balance(&owner, &keys.mint_a).await,
+    RESERVE_A - amount + ask_amount
+  );
+  assert!(ask_amount < amount);
+  assert_eq!(env.balance(&owner, &keys.mint_b).await, RESERVE_B);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, after.reserve_a);
+  assert_eq!(pool_data.reserve_b, after.reserve_b);
+  assert_eq!(pool_data.tax_a, tax);
+  assert_eq!(pool_data.stats.swaps, 2);
+}
+
+#[tokio::test]
+async fn swap_with_referral() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let payer = env.payer();
+  let ix = update_fee(
+    &env.program_id,
+    &payer,
+    &keys.pool,
+    FEE_RATIO,
+    TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO),
+  );
+  env.process(&[ix], &[]).await.unwrap();
+  let referrer = Pubkey::new_unique();
+  let referrer_b = env.create_ata(&referrer, &keys.mint_b).await;
+  let referrers = register_referrers(&mut env, &keys.pool, &[referrer]).await;
+
+  let before = env.pool(&keys.pool).await;
+  let amount = 100_000_000;
+  let (ask_amount, fee, tax, after) = quote(&before, &keys.mint_a, &keys.mint_b, amount);
+  let referral = before.referral(fee, MAX_REFERRAL_RATIO).unwrap();
+  let ix = swap(
+    &env.program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    amount,
+    0,
+    Some((&referrers, &referrer, MAX_REFERRAL_RATIO)),
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  assert_eq!(env.token_balance(&referrer_b).await, referral);
+  assert_eq!(
+    env.balance(&trader.pubkey(), &keys.mint_b).await,
+    RESERVE_B + ask_amount
+  );
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, after.reserve_a);
+  assert_eq!(pool_data.reserve_b, after.reserve_b - referral);
+  assert_eq!(pool_data.tax_b, tax);
+  assert_eq!(pool_data.stats.fee_b, (fee - referral) as u128);
+  assert_eq!(
+    env.token_balance(&keys.treasury_b).await,
+    pool_data.reserve_b + tax
+  );
+}
+
+#[tokio::test]
+async fn swap_errors() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let owner = trader.pubkey();
+  let (a, b) = (keys.mint_a, keys.mint_b);
+
+  let mut ix = swap(&program_id, &owner, &keys, &a, &b, 1_000, 0, None);
+  ix.accounts[0].is_signer = false;
+  assert_app_error(env.process(&[ix], &[]).await, AppError::InvalidOwner);
+
+  let mut ix = swap(&program_id, &owner, &keys, &a, &b, 1_000, 0, None);
+  ix.accounts[1].pubkey = a;
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::IncorrectProgramId,
+  );
+
+  let ix = swap(&program_id, &owner, &keys, &a, &a, 1_000, 0, None);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::SameMint);
+
+  let ix = swap(&program_id, &owner, &keys, &a, &b, 0, 0, None);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::ZeroValue);
+
+  let mut ix = swap(&program_id, &owner, &keys, &a, &b, 1_000, 0, None);
+  for _ in 0..4 {
+    ix.accounts
+      .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+  }
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::InvalidInstruction,
+  );
+
+  let ix = swap(&program_id, &owner, &keys, &a, &b, u64::MAX, 0, None);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::Overflow);
+
+  // An unknown mint has no reserve to quote
+  let c = Pubkey::new_unique();
+  let ix = swap(&program_id, &owner, &keys, &c, &b, 1_000, 0, None);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::Overflow);
+
+  let before = env.pool(&keys.pool).await;
+  let (ask_amount, _, _, _) = quote(&before, &a, &b, 1_000_000);
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &a,
+    &b,
+    1_000_000,
+    ask_amount + 1,
+    None,
+  );
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::ExceedLimit);
+
+  let payer = env.payer();
+  let ix = freeze_pool(&program_id, &payer, &keys.pool, PAUSE_SWAP);
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = swap(&program_id, &owner, &keys, &a, &b, 1_000, 0, None);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::FrozenPool);
+
+  // Nothing moved
+  assert_eq!(env.balance(&owner, &a).await, RESERVE_A);
+  assert_eq!(env.balance(&owner, &b).await, RESERVE_B);
+  let pool_data = env.pool(&keys.pool).await;
+  assert_eq!(pool_data.reserve_a, RESERVE_A);
+  assert_eq!(pool_data.reserve_b, RESERVE_B);
+  assert_eq!(pool_data.stats.swaps, 0);
+}
+
+#[tokio::test]
+async fn swap_referral_needs_referrer() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let payer = env.payer();
+  let ix = update_fee(
+    &env.program_id,
+    &payer,
+    &keys.pool,
+    FEE_RATIO,
+    TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO),
+  );
+  env.process(&[ix], &[]).await.unwrap();
+  // A referral ratio without the referrer account
+  let mut ix = swap(
+    &env.program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    1_000,
+    0,
+    None,
+  );
+  ix.data.extend_from_slice(&MAX_REFERRAL_RATIO.to_le_bytes());
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::InvalidInstruction,
+  );
+}
+
+#[tokio::test]
+async fn swap_referral_needs_registration() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let owner = trader.pubkey();
+  let (a, b) = (keys.mint_a, keys.mint_b);
+  let referrer = Pubkey::new_unique();
+  env.create_ata(&referrer, &b).await;
+  let referrers = register_referrers(&mut env, &keys.pool, &[referrer]).await;
+
+  // The pool takes no referral yet
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &a,
+    &b,
+    1_000,
+    0,
+    Some((&referrers, &referrer, 1)),
+  );
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidRatio);
+
+  let payer = env.payer();
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &keys.pool,
+    FEE_RATIO,
+    TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO),
+  );
+  env.process(&[ix], &[]).await.unwrap();
+
+  // The trader cannot refer themselves
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &a,
+    &b,
+    1_000_000,
+    0,
+    Some((&referrers, &owner, MAX_REFERRAL_RATIO)),
+  );
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidOwner);
+
+  // Nor name another list of referrers
+  let other = Keypair::new();
+  let ix = initialize_referrers(&program_id, &payer, &other.pubkey(), &payer, &[owner]);
+  env.process(&[ix], &[&other]).await.unwrap();
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &a,
+    &b,
+    1_000_000,
+    0,
+    Some((&other.pubkey(), &owner, MAX_REFERRAL_RATIO)),
+  );
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::InvalidInstruction,
+  );
+
+  // Until the authority registers them
+  let ix = update_referrers(&program_id, &payer, &referrers, &[referrer, owner]);
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &a,
+    &b,
+    1_000_000,
+    0,
+    Some((&referrers, &owner, MAX_REFERRAL_RATIO)),
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  // Detached, the pool pays no referral
+  let ix = set_referrers(&program_id, &payer, &keys.pool, &system_program::id());
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = swap(
+    &program_id,
+    &owner,
+    &keys,
+    &a,
+    &b,
+    1_000_000,
+    0,
+    Some((&referrers, &owner, MAX_REFERRAL_RATIO)),
+  );
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::InvalidInstruction,
+  );
+}
+
+#[tokio::test]
+async fn swap_with_staked_fee_tier() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let owner = trader.pubkey();
+  let (a, b) = (keys.mint_a, keys.mint_b);
+  let governance = env.create_mint().await;
+  env.mint_to(&owner, &governance, STAKE).await;
+  let fee_tiers = Keypair::new();
+  let ix = initialize_fee_tiers(
+    &program_id,
+    &payer,
+    &fee_tiers.pubkey(),
+    &payer,
+    &governance,
+    &[(STAKE, DISCOUNT)],
+  );
+  env.process(&[ix], &[&fee_tiers]).await.unwrap();
+  let ix = set_fee_tiers(&program_id, &payer, &keys.pool, &fee_tiers.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let stake_acc = pool_keypair(&program_id);
+  let tiered = |amount: u64| {
+    let mut ix = swap(&program_id, &owner, &keys, &a, &b, amount, 0, None);
+    ix.accounts
+      .push(AccountMeta::new_readonly(fee_tiers.pubkey(), false));
+    ix.accounts
+      .push(AccountMeta::new_readonly(stake_acc.pubkey(), false));
+    ix
+  };
+  let amount = 100_000_000;
+
+  // A fresh stake earns nothing, even in the transaction that swaps
+  let before = env.pool(&keys.pool).await;
+  let (ask_amount, _, _, _) = quote(&before, &a, &b, amount);
+  let ix = stake(&program_id, &owner, &stake_acc.pubkey(), &governance, STAKE);
+  env
+    .process(&[ix, tiered(amount)], &[&trader, &stake_acc])
+    .await
+    .unwrap();
+  assert_eq!(env.balance(&owner, &b).await, RESERVE_B + ask_amount);
+  assert_eq!(env.balance(&owner, &governance).await, 0);
+
+  // Held for the holding period, it reaches the tier
+  env.warp(HOLDING_SLOTS).await;
+  let before = env.pool(&keys.pool).await;
+  let (temp_ask_amount, _, _) = before.curve(amount, &a, &b).unwrap();
+  let (undiscounted, _, _) = before.fee(temp_ask_amount).unwrap();
+  let (discounted, _, _) = before
+    .discounted_fee(temp_ask_amount, DISCOUNT, false)
+    .unwrap();
+  assert!(discounted > undiscounted);
+  let balance = env.balance(&owner, &b).await;
+  env.process(&[tiered(amount)], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&owner, &b).await, balance + discounted);
+
+  // Unstaked, the tokens come back and the discount is gone
+  let ix = unstake(&program_id, &owner, &stake_acc.pubkey(), &governance, STAKE);
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&owner, &governance).await, STAKE);
+  let before = env.pool(&keys.pool).await;
+  let (ask_amount, _, _, _) = quote(&before, &a, &b, amount);
+  let balance = env.balance(&owner, &b).await;
+  env.process(&[tiered(amount)], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&owner, &b).await, balance + ask_amount);
+}
+
+#[tokio::test]
+async fn stake_errors() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let owner = trader.pubkey();
+  let governance = env.create_mint().await;
+  env.mint_to(&owner, &governance, STAKE).await;
+  let stake_acc = pool_keypair(&program_id);
+  let stake_key = stake_acc.pubkey();
+
+  let ix = stake(&program_id, &owner, &stake_key, &governance, 0);
+  assert_app_error(
+    env.process(&[ix], &[&trader, &stake_acc]).await,
+    AppError::ZeroValue,
+  );
+
+  // The vault must be the treasurer's associated account
+  let mut ix = stake(&program_id, &owner, &stake_key, &governance, STAKE);
+  ix.accounts[4].pubkey = get_associated_token_address(&owner, &governance);
+  assert_app_error(
+    env.process(&[ix], &[&trader, &stake_acc]).await,
+    AppError::InvalidStake,
+  );
+
+  let ix = stake(&program_id, &owner, &stake_key, &governance, STAKE / 2);
+  env.process(&[ix], &[&trader, &stake_acc]).await.unwrap();
+
+  // Only the owner tops up or withdraws
+  let other = env.create_wallet().await;
+  env.mint_to(&other.pubkey(), &governance, STAKE).await;
+  let ix = stake(&program_id, &other.pubkey(), &stake_key, &governance, 1);
+  assert_app_error(
+    env.process(&[ix], &[&other, &stake_acc]).await,
+    AppError::InvalidOwner,
+  );
+  let ix = unstake(&program_id, &other.pubkey(), &stake_key, &governance, 1);
+  assert_app_error(env.process(&[ix], &[&other]).await, AppError::InvalidOwner);
+
+  // In the mint of the stake only
+  let ix = stake(&program_id, &owner, &stake_key, &keys.mint_a, 1);
+  assert_app_error(
+    env.process(&[ix], &[&trader, &stake_acc]).await,
+    AppError::InvalidStake,
+  );
+
+  let ix = unstake(&program_id, &owner, &stake_key, &governance, STAKE);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::Overflow);
+
+  // A stake of someone else earns no discount
+  let fee_tiers = Keypair::new();
+  let payer = env.payer();
+  let ix = initialize_fee_tiers(
+    &program_id,
+    &payer,
+    &fee_tiers.pubkey(),
+    &payer,
+    &governance,
+    &[(1, DISCOUNT)],
+  );
+  env.process(&[ix], &[&fee_tiers]).await.unwrap();
+  let ix = set_fee_tiers(&program_id, &payer, &keys.pool, &fee_tiers.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let (a, b) = (keys.mint_a, keys.mint_b);
+  let mut ix = swap(&program_id, &other.pubkey(), &keys, &a, &b, 1_000, 0, None);
+  ix.accounts
+    .push(AccountMeta::new_readonly(fee_tiers.pubkey(), false));
+  ix.accounts
+    .push(AccountMeta::new_readonly(stake_key, false));
+  assert_app_error(env.process(&[ix], &[&other]).await, AppError::InvalidOwner);
+}
+
+#[tokio::test]
+async fn swap_allowlisted_program() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let program_id = env.program_id;
+  let partner_id = env.partner_id;
+  let payer = env.payer();
+  let owner = trader.pubkey();
+  let (a, b) = (keys.mint_a, keys.mint_b);
+  let allowlist = Keypair::new();
+  let ix = initialize_allowlist(&program_id, &payer, &allowlist.pubkey(), &payer, DISCOUNT);
+  env.process(&[ix], &[&allowlist]).await.unwrap();
+  let ix = update_allowlist(
+    &program_id,
+    &payer,
+    &allowlist.pubkey(),
+    DISCOUNT,
+    &[partner_id],
+  );
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = set_allowlist(&program_id, &payer, &keys.pool, &allowlist.pubkey());
+  env.process(&[ix], &[]).await.unwrap();
+  let allowlisted = |amount: u64| {
+    let mut ix = swap(&program_id, &owner, &keys, &a, &b, amount, 0, None);
+    ix.accounts
+      .push(AccountMeta::new_readonly(sysvar::instructions::id(), false));
+    ix.accounts
+      .push(AccountMeta::new_readonly(allowlist.pubkey(), false));
+    ix
+  };
+  let amount = 100_000_000;
+
+  // The trader alone is not allowlisted
+  assert_app_error(
+    env.process(&[allowlisted(amount)], &[&trader]).await,
+    AppError::InvalidOwner,
+  );
+  // Nor is the partner without the instructions sysvar
+  let mut ix = allowlisted(amount);
+  ix.accounts.remove(ix.accounts.len() - 2);
+  assert_app_error(
+    env
+      .process(&[through_partner(&partner_id, ix)], &[&trader])
+      .await,
+    AppError::InvalidOwner,
+  );
+
+  // Through the partner, the swap skips the tax at the discounted fee
+  let before = env.pool(&keys.pool).await;
+  let (temp_ask_amount, _, _) = before.curve(amount, &a, &b).unwrap();
+  let (ask_amount, _, tax) = before
+    .discounted_fee(temp_ask_amount, DISCOUNT, true)
+    .unwrap();
+  assert_eq!(tax, 0);
+  let ix = through_partner(&partner_id, allowlisted(amount));
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(env.balance(&owner, &b).await, RESERVE_B + ask_amount);
+  assert_eq!(env.pool(&keys.pool).await.tax_b, 0);
+}
+
+#[tokio::test]
+async fn swap_locked_pool() {
+  let pool = Pubkey::new_unique();
+  let (mint_a, mint_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+  let mut env = Env::start_with(|program_test, program_id| {
+    let pool_data = Pool {
+      owner: Pubkey::new_unique(),
+      state: PoolState::Initialized,
+      mint_a,
+      reserve_a: RESERVE_A,
+      mint_b,
+      reserve_b: RESERVE_B,
+      locked: true,
+      ..Pool::default()
+    };
+    program_test.add_account(pool, pool_account(program_id, &pool_data));
+  })
+  .await;
+  let trader = env.create_wallet().await;
+  let keys = PoolKeys {
+    pool,
+    treasurer: Pubkey::new_unique(),
+    mint_lpt: Pubkey::new_unique(),
+    mint_a,
+    treasury_a: Pubkey::new_unique(),
+    mint_b,
+    treasury_b: Pubkey::new_unique(),
+  };
+  let ix = swap(
+    &env.program_id,
+    &trader.pubkey(),
+    &keys,
+    &mint_a,
+    &mint_b,
+    1_000,
+    0,
+    None,
+  );
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::LockedPool);
+}
+
+#[tokio::test]
+async fn swap_is_paused_separately() {
+  let (mut env, keys, trader) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let payer = env.payer();
+  let ix = freeze_pool(&env.program_id, &payer, &keys.pool, PAUSE_DEPOSIT);
+  env.process(&[ix], &[]).await.unwrap();
+  let ix = swap(
+    &env.program_id,
+    &trader.pubkey(),
+    &keys,
+    &keys.mint_a,
+    &keys.mint_b,
+    1_000_000,
+    0,
+    None,
+  );
+  env.process(&[ix], &[&trader]).await.unwrap();
+  assert_eq!(env.pool(&keys.pool).await.stats.swaps, 1);
+}
+
+///
+/// Pools A/B and B/C, and a router over them
+///
+async fn setup_route() -> (Env, PoolKeys, PoolKeys, Router) {
+  let (mut env, ab, _) = setup(RESERVE_A, RESERVE_B, FEE_RATIO, TAX_RATIO).await;
+  let mint_c = env.create_mint().await;
+  let bc = env
+    .create_pool(
+      &ab.mint_b, &mint_c, RESERVE_B, RESERVE_C, FEE_RATIO, TAX_RATIO,
+    )
+    .await;
+  let pools = [
+    (ab.pool, env.pool(&ab.pool).await),
+    (bc.pool, env.pool(&bc.pool).await),
+  ];
+  (env, ab, bc, Router::new(&pools))
+}
+
+#[tokio::test]
+async fn route_two_hops() {
+  let (mut env, ab, bc, router) = setup_route().await;
+  let trader = newcomer(&mut env, &ab).await;
+  let amount = 100_000_000;
+  let path = router
+    .quote(
+      &[
+        (ab.pool, ab.mint_a, ab.mint_b),
+        (bc.pool, bc.mint_a, bc.mint_b),
+      ],
+      amount,
+    )
+    .unwrap();
+  let ask_amount = path.ask_amount();
+  let ix = router
+    .route_instruction(&env.program_id, &trader.pubkey(), &path, ask_amount)
+    .unwrap();
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  let owner = trader.pubkey();
+  assert_eq!(env.balance(&owner, &ab.mint_a).await, RESERVE_A - amount);
+  // The intermediate B passed through a fresh account
+  assert_eq!(env.balance(&owner, &ab.mint_b).await, 0);
+  assert_eq!(env.balance(&owner, &bc.mint_b).await, ask_amount);
+
+  let ab_before = router.pools[&ab.pool];
+  let (b_amount, _, tax_b, ab_after) = quote(&ab_before, &ab.mint_a, &ab.mint_b, amount);
+  assert_eq!(path.hops[0].ask_amount, b_amount);
+  let ab_data = env.pool(&ab.pool).await;
+  assert_eq!(ab_data.reserve_a, ab_after.reserve_a);
+  assert_eq!(ab_data.reserve_b, ab_after.reserve_b);
+  assert_eq!(ab_data.tax_b, tax_b);
+
+  let bc_before = router.pools[&bc.pool];
+  let (c_amount, _, tax_c, bc_after) = quote(&bc_before, &bc.mint_a, &bc.mint_b, b_amount);
+  assert_eq!(c_amount, ask_amount);
+  let bc_data = env.pool(&bc.pool).await;
+  assert_eq!(bc_data.reserve_a, bc_after.reserve_a);
+  assert_eq!(bc_data.reserve_b, bc_after.reserve_b);
+  assert_eq!(bc_data.tax_b, tax_c);
+  assert!(!ab_data.locked && !bc_data.locked);
+}
+
+#[tokio::test]
+async fn route_with_referral_and_hop_limits() {
+  let (mut env, ab, bc, router) = setup_route().await;
+  let program_id = env.program_id;
+  let payer = env.payer();
+  let ix = update_fee(
+    &program_id,
+    &payer,
+    &bc.pool,
+    FEE_RATIO,
+    TAX_RATIO,
+    Some(MAX_REFERRAL_RATIO),
+  );
+  env.process(&[ix], &[]).await.unwrap();
+  let referrer = Pubkey::new_unique();
+  let referrer_c = env.create_ata(&referrer, &bc.mint_b).await;
+  let referrers = register_referrers(&mut env, &bc.pool, &[referrer]).await;
+  let trader = newcomer(&mut env, &ab).await;
+  let amount = 100_000_000;
+  let path = router
+    .quote(
+      &[
+        (ab.pool, ab.mint_a, ab.mint_b),
+        (bc.pool, bc.mint_a, bc.mint_b),
+      ],
+      amount,
+    )
+    .unwrap();
+  let bc_before = env.pool(&bc.pool).await;
+  let b_amount = path.hops[0].ask_amount;
+  let (c_amount, fee, _, _) = quote(&bc_before, &bc.mint_a, &bc.mint_b, b_amount);
+  let referral = bc_before.referral(fee, MAX_REFERRAL_RATIO).unwrap();
+
+  // RouteWithReferral takes the referral ratio ahead of the limit of each hop
+  let mut ix = router
+    .route_instruction(&program_id, &trader.pubkey(), &path, c_amount)
+    .unwrap();
+  ix.data[0] = 36;
+  ix.data.extend_from_slice(&MAX_REFERRAL_RATIO.to_le_bytes());
+  ix.data.extend_from_slice(&b_amount.to_le_bytes());
+  ix.data.extend_from_slice(&c_amount.to_le_bytes());
+  ix.accounts
+    .push(AccountMeta::new_readonly(referrers, false));
+  ix.accounts.push(AccountMeta::new(referrer_c, false));
+  env.process(&[ix], &[&trader]).await.unwrap();
+
+  assert_eq!(env.token_balance(&referrer_c).await, referral);
+  assert_eq!(env.balance(&trader.pubkey(), &bc.mint_b).await, c_amount);
+  let bc_data = env.pool(&bc.pool).await;
+  assert_eq!(bc_data.stats.fee_b, (fee - referral) as u128);
+}
+
+#[tokio::test]
+async fn route_referral_needs_referrer() {
+  let (mut env, ab, bc, router) = setup_route().await;
+  let trader = newcomer(&mut env, &ab).await;
+  let path = router
+    .quote(
+      &[
+        (ab.pool, ab.mint_a, ab.mint_b),
+        (bc.pool, bc.mint_a, bc.mint_b),
+      ],
+      1_000_000,
+    )
+    .unwrap();
+  let mut ix = router
+    .route_instruction(&env.program_id, &trader.pubkey(), &path, 0)
+    .unwrap();
+  ix.data[0] = 36;
+  ix.data.extend_from_slice(&1u64.to_le_bytes());
+  assert_app_error(
+    env.process(&[ix], &[&trader]).await,
+    AppError::InvalidInstruction,
+  );
+}
+
+#[tokio::test]
+async fn route_errors() {
+  let (mut env, ab, bc, router) = setup_route().await;
+  let program_id = env.program_id;
+  let trader = newcomer(&mut env, &ab).await;
+  let owner = trader.pubkey();
+  let legs = [
+    (ab.pool, ab.mint_a, ab.mint_b),
+    (bc.pool, bc.mint_a, bc.mint_b),
+  ];
+  let path = router.quote(&legs, 1_000_000).unwrap();
+  let route = router
+    .route_instruction(&program_id, &owner, &path, 0)
+    .unwrap();
+
+  // No hop
+  let mut ix = route.clone();
+  ix.accounts.truncate(5);
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidRoute);
+
+  // Neither a whole hop nor a referrer
+  let mut ix = route.clone();
+  ix.accounts.pop();
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidRoute);
+
+  // One limit for two hops
+  let mut ix = route.clone();
+  ix.data.extend_from_slice(&0u64.to_le_bytes());
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidRoute);
+
+  // The second hop doesn't bid what the first asked
+  let mut ix = route.clone();
+  ix.accounts[5 + 8 + 1].pubkey = Pubkey::new_unique();
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidRoute);
+
+  // The same pool twice
+  let back = router
+    .quote(
+      &[
+        (ab.pool, ab.mint_a, ab.mint_b),
+        (ab.pool, ab.mint_b, ab.mint_a),
+      ],
+      1_000_000,
+    )
+    .unwrap();
+  let ix = router
+    .route_instruction(&program_id, &owner, &back, 0)
+    .unwrap();
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::InvalidRoute);
+
+  // The final output is below the limit
+  let ix = router
+    .route_instruction(&program_id, &owner, &path, path.ask_amount() + 1)
+    .unwrap();
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::ExceedLimit);
+
+  // A hop output is below its limit
+  let mut ix = route.clone();
+  ix.data
+    .extend_from_slice(&(path.hops[0].ask_amount + 1).to_le_bytes());
+  ix.data.extend_from_slice(&0u64.to_le_bytes());
+  assert_app_error(env.process(&[ix], &[&trader]).await, AppError::ExceedLimit);
+
+  // A frozen pool on the way
+  let payer = env.payer();
+  let ix = freeze_pool(&program_id, &payer, &bc.pool, PAUSE_SWAP);
+  env.process(&[ix], &[]).await.unwrap();
+  assert_app_error(
+    env.process(&[route], &[&trader]).await,
+    AppError::FrozenPool,
+  );
+
+  // Nothing moved
+  assert_eq!(env.balance(&owner, &ab.mint_a).await, RESERVE_A);
+  assert_eq!(env.balance(&owner, &ab.mint_b).await, 0);
+  assert_eq!(env.pool(&ab.pool).await.reserve_a, RESERVE_A);
+  assert_eq!(env.pool(&bc.pool).await.reserve_a, RESERVE_B);
+}